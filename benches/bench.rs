@@ -1,3 +1,6 @@
+// The loops index `buffer` directly on purpose, to benchmark raw softbuffer usage.
+#![allow(clippy::needless_range_loop)]
+
 use std::slice;
 use std::time::Instant;
 
@@ -9,13 +12,15 @@ use winit::event::{StartCause, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, EventLoop};
 use winit::window::{Window, WindowAttributes, WindowId};
 
-use softbuffer_rgb::RgbBuffer;
+use softbuffer_rgb::{Canvas, RgbBuffer};
 
 const X: usize = 400;
 const Y: usize = 300;
 const RANDOM_COLORS: &[u8; X * Y * 3] = include_bytes!("colors");
 const HELLO_WORLD: &[u8; 87528] = include_bytes!("hello_world");
 const ITS: usize = 10;
+const HD_X: usize = 1920;
+const HD_Y: usize = 1080;
 
 fn main() {
     let mut app = App::default();
@@ -41,6 +46,10 @@ impl ApplicationHandler for App {
             let window = self.window.as_ref().unwrap();
             let context = Context::new(window).unwrap();
             let mut surface = Surface::new(&context, &window).unwrap();
+
+            // Fill a large buffer.
+            fill_hd(&mut surface);
+
             surface
                 .resize(
                     NonZeroU32::new(X as u32).unwrap(),
@@ -67,11 +76,13 @@ impl ApplicationHandler for App {
 
             // Raw softbuffer.
             let mut t0 = Instant::now();
-            for x in 0..X {
-                for y in 0..Y {
-                    rgb_buffer.buffer[index(x, y)] = u32s[x][y];
+            rgb_buffer.with_buffer(|buffer| {
+                for x in 0..X {
+                    for y in 0..Y {
+                        buffer[index(x, y)] = u32s[x][y];
+                    }
                 }
-            }
+            });
             println!("softbuffer: {}s", (Instant::now() - t0).as_secs_f64());
 
             // Raw pixels.
@@ -83,13 +94,22 @@ impl ApplicationHandler for App {
                 }
             }
             t0 = Instant::now();
+            let pixels = rgb_buffer.pixels_mut();
             for x in 0..X {
                 for y in 0..Y {
-                    rgb_buffer.pixels[y][x] = rgb4[x][y];
+                    pixels[y][x] = rgb4[x][y];
                 }
             }
             println!("softbuffer-rgb: {}s", (Instant::now() - t0).as_secs_f64());
 
+            // `load_rgb`
+            t0 = Instant::now();
+            rgb_buffer.load_rgb(RANDOM_COLORS).unwrap();
+            println!(
+                "softbuffer-rgb (load_rgb): {}s",
+                (Instant::now() - t0).as_secs_f64()
+            );
+
             // Blit "Hello World!" to the buffer.
             // Convert raw bytes to values.
             let mut positions = vec![];
@@ -100,20 +120,22 @@ impl ApplicationHandler for App {
                 ]);
             }
 
-            println!("");
+            println!();
             println!("Set multiple pixels of the same color:");
 
             // Softbuffer.
             t0 = Instant::now();
-            for position in positions.iter() {
-                rgb_buffer.buffer[index(position[0], position[1])] = 0;
-            }
+            rgb_buffer.with_buffer(|buffer| {
+                for position in positions.iter() {
+                    buffer[index(position[0], position[1])] = 0;
+                }
+            });
             println!("softbuffer: {}s", (Instant::now() - t0).as_secs_f64());
 
             // `set_pixels`
             let hello_world_color = [0, 0, 0, 0];
             t0 = Instant::now();
-            let _ = rgb_buffer.set_pixels(&positions, hello_world_color);
+            rgb_buffer.set_pixels(&positions, hello_world_color);
             println!(
                 "softbuffer-rbg (set_pixels): {}s",
                 (Instant::now() - t0).as_secs_f64()
@@ -125,17 +147,19 @@ impl ApplicationHandler for App {
             let h = 100;
             let color = [0, 255, 20, 5];
             let sb_color = u32::from_le_bytes(color);
-            println!("");
+            println!();
             println!("Draw a rectangle:");
             // Test raw softbuffer.
             let mut dts = [0.0; ITS];
             for dt in dts.iter_mut() {
                 let t0 = Instant::now();
-                for x1 in x..x + w {
-                    for y1 in y..y + h {
-                        rgb_buffer.buffer[index(x1, y1)] = sb_color;
+                rgb_buffer.with_buffer(|buffer| {
+                    for x1 in x..x + w {
+                        for y1 in y..y + h {
+                            buffer[index(x1, y1)] = sb_color;
+                        }
                     }
-                }
+                });
                 *dt = (Instant::now() - t0).as_secs_f64();
             }
             println!(
@@ -152,11 +176,31 @@ impl ApplicationHandler for App {
                 "softbuffer-rgb: {}s",
                 dts.iter().sum::<f64>() / dts.iter().len() as f64
             );
+            let mut dts = [0.0; ITS];
+            for dt in dts.iter_mut() {
+                let t0 = Instant::now();
+                rgb_buffer.fill_rectangle_u32(x, y, w, h, sb_color);
+                *dt = (Instant::now() - t0).as_secs_f64();
+            }
+            println!(
+                "softbuffer-rgb (u32): {}s",
+                dts.iter().sum::<f64>() / dts.iter().len() as f64
+            );
 
-            println!("");
+            let mut dts = [0.0; ITS];
+            for dt in dts.iter_mut() {
+                let t0 = Instant::now();
+                rgb_buffer.clear_rectangle(x, y, w, h);
+                *dt = (Instant::now() - t0).as_secs_f64();
+            }
+            println!(
+                "softbuffer-rgb (clear): {}s",
+                dts.iter().sum::<f64>() / dts.iter().len() as f64
+            );
+            println!();
             println!("Fill screen:");
             t0 = Instant::now();
-            rgb_buffer.buffer.fill(sb_color);
+            rgb_buffer.with_buffer(|buffer| buffer.fill(sb_color));
             println!("softbuffer: {}s", (Instant::now() - t0).as_secs_f64());
             t0 = Instant::now();
             rgb_buffer.fill(color);
@@ -169,6 +213,44 @@ impl ApplicationHandler for App {
     fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
 }
 
+/// Compare scalar and `softbuffer-rgb` fills of a 1920x1080 buffer.
+/// Run with `--features simd` to benchmark the SIMD path.
+fn fill_hd(surface: &mut Surface<&Window, &&Window>) {
+    surface
+        .resize(
+            NonZeroU32::new(HD_X as u32).unwrap(),
+            NonZeroU32::new(HD_Y as u32).unwrap(),
+        )
+        .unwrap();
+    let mut rgb_buffer =
+        RgbBuffer::<HD_X, HD_Y, _, _>::from_softbuffer(surface.buffer_mut().unwrap()).unwrap();
+    let color = [0, 255, 20, 5];
+    let sb_color = u32::from_le_bytes(color);
+    println!("Fill {HD_X}x{HD_Y} screen:");
+    let mut dts = [0.0; ITS];
+    for dt in dts.iter_mut() {
+        let t0 = Instant::now();
+        rgb_buffer.with_buffer(|buffer| buffer.fill(sb_color));
+        *dt = (Instant::now() - t0).as_secs_f64();
+    }
+    println!(
+        "scalar: {}s",
+        dts.iter().sum::<f64>() / dts.iter().len() as f64
+    );
+    let mut dts = [0.0; ITS];
+    for dt in dts.iter_mut() {
+        let t0 = Instant::now();
+        rgb_buffer.fill(color);
+        *dt = (Instant::now() - t0).as_secs_f64();
+    }
+    println!(
+        "softbuffer-rgb (simd: {}): {}s",
+        cfg!(feature = "simd"),
+        dts.iter().sum::<f64>() / dts.iter().len() as f64
+    );
+    println!();
+}
+
 #[inline]
 fn index(x: usize, y: usize) -> usize {
     y * X + x
@@ -0,0 +1,69 @@
+use crate::{Canvas, Color};
+
+/// An `X` by `Y` buffer of floating-point colors, e.g. for accumulating many additive light samples before displaying them.
+/// Unlike adding colors to a `Canvas`, channels don't clip at 255 while samples are accumulated.
+///
+/// Call `resolve_into` to tone-map the accumulated colors into a displayable buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccumBuffer<const X: usize, const Y: usize> {
+    pixels: Box<[[[f32; 4]; X]]>,
+}
+
+impl<const X: usize, const Y: usize> AccumBuffer<X, Y> {
+    /// Returns a new buffer where every pixel is 0.
+    pub fn new() -> Self {
+        Self {
+            pixels: vec![[[0.0; 4]; X]; Y].into_boxed_slice(),
+        }
+    }
+
+    /// Returns the accumulated pixels as `Y` rows of `X` colors. Each color is `[0, r, g, b]`, where 1 is the brightness of a channel value of 255.
+    pub fn pixels(&self) -> &[[[f32; 4]; X]] {
+        &self.pixels
+    }
+
+    /// Returns the accumulated pixels as `Y` mutable rows of `X` colors.
+    pub fn pixels_mut(&mut self) -> &mut [[[f32; 4]; X]] {
+        &mut self.pixels
+    }
+
+    /// Set every pixel to 0, e.g. at the start of a frame.
+    pub fn clear(&mut self) {
+        self.pixels.fill([[0.0; 4]; X]);
+    }
+
+    /// Add a color to the pixel at `(x, y)`.
+    ///
+    /// - `x` and `y` are the coordinates of the pixel.
+    /// - `color` is the `[0, r, g, b]` color. A channel value of 255 adds 1.
+    ///
+    /// If the position is out of bounds, this does nothing.
+    pub fn add(&mut self, x: usize, y: usize, color: Color) {
+        if let Some(pixel) = self.pixels.get_mut(y).and_then(|cols| cols.get_mut(x)) {
+            for (p, c) in pixel.iter_mut().zip(color).skip(1) {
+                *p += c as f32 / 255.0;
+            }
+        }
+    }
+
+    /// Tone-map every accumulated pixel into `dst`.
+    ///
+    /// - `dst` is the displayable buffer, e.g. an `RgbBuffer`. Every pixel is replaced.
+    /// - `exposure` is the brightness. Each channel `v` becomes `1 - exp(-v * exposure)`, scaled to 255.
+    ///
+    /// Bright pixels approach 255 smoothly instead of clipping.
+    pub fn resolve_into(&self, dst: &mut impl Canvas<X, Y>, exposure: f32) {
+        let tone_map = |v: f32| ((1.0 - (-v * exposure).exp()) * 255.0).round() as u8;
+        for (dst, src) in dst.pixels_mut().iter_mut().zip(self.pixels.iter()) {
+            for (c, v) in dst.iter_mut().zip(src) {
+                *c = [0, tone_map(v[1]), tone_map(v[2]), tone_map(v[3])];
+            }
+        }
+    }
+}
+
+impl<const X: usize, const Y: usize> Default for AccumBuffer<X, Y> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,2799 @@
+use std::io;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "png")]
+use std::fs::File;
+#[cfg(feature = "png")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "png")]
+use std::path::Path;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "simd")]
+use wide::u32x8;
+
+use crate::line::partition_point;
+use crate::{
+    blend, font, from_u32, line_points, luminance, rgb, to_u32, BlendMode, Channel, Color,
+    DrawContext, Encoder, MarkerShape, Mask, Rect, Rgb, RgbBufferError, RgbSubView, Scratch, WHITE,
+};
+
+/// The number of rows per chunk in parallel methods.
+#[cfg(feature = "rayon")]
+const PAR_ROWS: usize = 8;
+
+/// The 4x4 Bayer matrix that is used by `Canvas::dither_ordered`.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Drawing methods that are shared by every `X` by `Y` pixel buffer, such as `RgbBuffer` and `OwnedRgbBuffer`.
+///
+/// Implementors only need to provide access to their rows of pixels.
+/// Every other method has a default implementation.
+pub trait Canvas<const X: usize, const Y: usize> {
+    /// Returns the pixels as `Y` rows of `X` colors. To get the pixel at `x=4, y=5`: `self.pixels()[y][x]`.
+    fn pixels(&self) -> &[[Color; X]];
+
+    /// Returns the pixels as `Y` mutable rows of `X` colors. To set the pixel at `x=4, y=5`: `self.pixels_mut()[y][x] = [0, 200, 160, 30];`
+    fn pixels_mut(&mut self) -> &mut [[Color; X]];
+
+    /// Returns the width of the buffer, `X`.
+    fn width(&self) -> usize {
+        X
+    }
+
+    /// Returns the height of the buffer, `Y`.
+    fn height(&self) -> usize {
+        Y
+    }
+
+    /// Returns the width and height of the buffer, `(X, Y)`.
+    fn dimensions(&self) -> (usize, usize) {
+        (X, Y)
+    }
+
+    /// Returns true if the pixel at `(x, y)` is inside the buffer.
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < X && y < Y
+    }
+
+    /// Returns true if every pixel of `rect` is inside the buffer.
+    fn contains_rect(&self, rect: Rect) -> bool {
+        rect.x.checked_add(rect.w).is_some_and(|x1| x1 <= X)
+            && rect.y.checked_add(rect.h).is_some_and(|y1| y1 <= Y)
+    }
+
+    /// Returns the color of the pixel at `(x, y)` without checking whether the position is in bounds.
+    /// This is for hot loops that have already validated their positions, e.g. with `contains_rect`.
+    ///
+    /// # Safety
+    ///
+    /// `x` must be less than `X`, and `y` must be less than `Y` and less than `self.pixels().len()`.
+    /// This is checked in debug builds.
+    unsafe fn pixel_unchecked(&self, x: usize, y: usize) -> Color {
+        debug_assert!(
+            x < X && y < Y && y < self.pixels().len(),
+            "Position out of bounds: ({x}, {y})"
+        );
+        *self.pixels().get_unchecked(y).get_unchecked(x)
+    }
+
+    /// Set the color of the pixel at `(x, y)` without checking whether the position is in bounds.
+    ///
+    /// # Safety
+    ///
+    /// `x` must be less than `X`, and `y` must be less than `Y` and less than `self.pixels().len()`.
+    /// This is checked in debug builds.
+    unsafe fn set_pixel_unchecked(&mut self, x: usize, y: usize, color: Color) {
+        debug_assert!(
+            x < X && y < Y && y < self.pixels().len(),
+            "Position out of bounds: ({x}, {y})"
+        );
+        *self.pixels_mut().get_unchecked_mut(y).get_unchecked_mut(x) = color;
+    }
+
+    /// Fill the buffer with an `[0, r, g, b]` color.
+    ///
+    /// If the `simd` feature is enabled, this writes eight pixels at a time.
+    fn fill(&mut self, color: Color) {
+        #[cfg(feature = "simd")]
+        {
+            let value = to_u32(color);
+            // Split the pixels into an unaligned head, aligned chunks of eight pixels, and an unaligned tail.
+            let (head, chunks, tail) =
+                unsafe { self.pixels_mut().as_flattened_mut().align_to_mut::<u32x8>() };
+            head.fill(color);
+            chunks.fill(u32x8::new([value; 8]));
+            tail.fill(color);
+        }
+        #[cfg(not(feature = "simd"))]
+        self.pixels_mut().fill([color; X]);
+    }
+
+    /// Fill the buffer with an `Rgb` color.
+    fn fill_rgb(&mut self, color: Rgb) {
+        self.fill(color.to_color());
+    }
+
+    /// Set the color of multiple pixels.
+    ///
+    /// - `positions`: A slice of `[x, y]` positions.
+    /// - `color`: The `[0, r, g, b]` color.
+    ///
+    /// Panics if any position in `positions` is out of bounds.
+    fn set_pixels(&mut self, positions: &[[usize; 2]], color: Color) {
+        let pixels = self.pixels_mut();
+        // Copy the color into each position.
+        for position in positions {
+            pixels[position[1]][position[0]] = color;
+        }
+    }
+
+    /// Set the color of multiple pixels, each with its own color.
+    ///
+    /// - `pixels`: A slice of `((x, y), color)` pairs.
+    ///
+    /// Panics if any position in `pixels` is out of bounds.
+    fn set_pixels_with_colors(&mut self, pixels: &[((usize, usize), Color)]) {
+        let dst = self.pixels_mut();
+        for &((x, y), color) in pixels {
+            dst[y][x] = color;
+        }
+    }
+
+    /// Set the color of multiple pixels, each with its own color.
+    ///
+    /// - `pixels`: A slice of `((x, y), color)` pairs.
+    ///
+    /// Returns an `Error` if any position in `pixels` is out of bounds. In that case, no pixels are set.
+    fn try_set_pixels_with_colors(
+        &mut self,
+        pixels: &[((usize, usize), Color)],
+    ) -> Result<(), RgbBufferError> {
+        if let Some(&((x, y), _)) = pixels.iter().find(|((x, y), _)| !self.contains(*x, *y)) {
+            return Err(RgbBufferError::InvalidPosition { x, y });
+        }
+        self.set_pixels_with_colors(pixels);
+        Ok(())
+    }
+
+    /// Fill a rectangle with a color.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Panics if the top-left or bottom-right positions are out of bounds.
+    fn fill_rectangle(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color) {
+        self.fill_rect(Rect::new(x, y, w, h), color);
+    }
+
+    /// Fill a rectangle with a color.
+    ///
+    /// - `rect` is the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Panics if the top-left or bottom-right positions are out of bounds.
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        if let Err(error) = self.try_fill_rect(rect, color) {
+            panic!("Can't fill {rect:?}: {error}");
+        }
+    }
+
+    /// Fill a rectangle with a color.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Returns an `Error` if the top-left or bottom-right positions are out of bounds. In that case, no pixels are filled.
+    fn try_fill_rectangle(
+        &mut self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        color: Color,
+    ) -> Result<(), RgbBufferError> {
+        self.try_fill_rect(Rect::new(x, y, w, h), color)
+    }
+
+    /// Fill a rectangle with a color.
+    ///
+    /// - `rect` is the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Returns an `Error` if the top-left or bottom-right positions are out of bounds. In that case, no pixels are filled.
+    fn try_fill_rect(&mut self, rect: Rect, color: Color) -> Result<(), RgbBufferError> {
+        if !self.contains_rect(rect) {
+            return Err(if self.contains(rect.x, rect.y) {
+                RgbBufferError::InvalidSize {
+                    x: rect.w,
+                    y: rect.h,
+                }
+            } else {
+                RgbBufferError::InvalidPosition {
+                    x: rect.x,
+                    y: rect.y,
+                }
+            });
+        }
+        let x1 = rect.x + rect.w;
+        // Create a row of colors and get a slice of it.
+        let colors = &[color; X][rect.x..x1];
+        // Fill the rectangle.
+        self.pixels_mut()[rect.y..rect.y + rect.h]
+            .iter_mut()
+            .for_each(|cols| cols[rect.x..x1].copy_from_slice(colors));
+        Ok(())
+    }
+
+    /// Fill a rectangle with rounded corners.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    /// - `radius` is the radius of each corner. It's clamped to `min(w, h) / 2`.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// The corners are rounded like `fill_circle`.
+    /// Pixels that are out of bounds aren't drawn.
+    fn fill_rounded_rectangle(
+        &mut self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        radius: usize,
+        color: Color,
+    ) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let pixels = self.pixels_mut();
+        let r = radius.min(w.min(h) / 2) as isize;
+        let (x, y, w, h) = (x as isize, y as isize, w as isize, h as isize);
+        for j in 0..h.min(Y as isize - y) {
+            // The vertical distance from the center of the nearest corner's circle.
+            let dy = if j < r {
+                r - j
+            } else if j >= h - r {
+                j - (h - 1 - r)
+            } else {
+                0
+            };
+            // Find the widest span that is inside the corner's circle.
+            let mut dx = r;
+            while dx > 0 && dx * dx + dy * dy > r * r + r {
+                dx -= 1;
+            }
+            let inset = r - dx;
+            fill_span::<X, Y>(pixels, y + j, x + inset, x + w - 1 - inset, color);
+        }
+    }
+
+    /// Fill rows `y..y + h` with a color. This is faster than `fill_rectangle` for full-width rectangles.
+    ///
+    /// - `y` is the first row.
+    /// - `h` is the number of rows.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Rows that are out of bounds aren't filled.
+    fn fill_rows(&mut self, y: usize, h: usize, color: Color) {
+        let y0 = y.min(Y);
+        let y1 = y.saturating_add(h).min(Y);
+        // Create a row of colors and copy it into each row.
+        let colors = [color; X];
+        self.pixels_mut()[y0..y1]
+            .iter_mut()
+            .for_each(|cols| cols.copy_from_slice(&colors));
+    }
+
+    /// Tile a pattern across the entire buffer, starting at the top-left corner.
+    ///
+    /// - `pattern_width` is the width of the pattern.
+    /// - `pattern` is the pattern's `[0, r, g, b]` colors, row by row.
+    ///
+    /// Returns an `Error` if `pattern` is empty or its length isn't a multiple of `pattern_width`.
+    fn fill_pattern(
+        &mut self,
+        pattern_width: usize,
+        pattern: &[Color],
+    ) -> Result<(), RgbBufferError> {
+        if pattern.is_empty() || !pattern.len().is_multiple_of(pattern_width) {
+            return Err(RgbBufferError::InvalidSize {
+                x: pattern_width,
+                y: pattern.len(),
+            });
+        }
+        let pattern_height = pattern.len() / pattern_width;
+        // Tile each row of the pattern across the first rows of the buffer.
+        let pixels = self.pixels_mut();
+        for (cols, pattern_row) in pixels.iter_mut().zip(pattern.chunks_exact(pattern_width)) {
+            cols.chunks_mut(pattern_width)
+                .for_each(|chunk| chunk.copy_from_slice(&pattern_row[..chunk.len()]));
+        }
+        // Copy the tiled rows down the rest of the buffer.
+        for y in pattern_height..Y {
+            pixels[y] = pixels[y - pattern_height];
+        }
+        Ok(())
+    }
+
+    /// Fill the buffer with vertical stripes, starting at the left edge with `color_a`.
+    ///
+    /// - `width` is the width of each stripe.
+    /// - `color_a` and `color_b` are the `[0, r, g, b]` colors of alternating stripes.
+    ///
+    /// Returns an `Error` if `width` is 0.
+    fn fill_vertical_stripes(
+        &mut self,
+        width: usize,
+        color_a: Color,
+        color_b: Color,
+    ) -> Result<(), RgbBufferError> {
+        if width == 0 {
+            return Err(RgbBufferError::InvalidSize { x: width, y: Y });
+        }
+        // Build one striped row and copy it to every row.
+        let row: [Color; X] = std::array::from_fn(|x| {
+            if (x / width).is_multiple_of(2) {
+                color_a
+            } else {
+                color_b
+            }
+        });
+        self.pixels_mut().fill(row);
+        Ok(())
+    }
+
+    /// Fill the buffer with horizontal stripes, starting at the top edge with `color_a`.
+    ///
+    /// - `height` is the height of each stripe.
+    /// - `color_a` and `color_b` are the `[0, r, g, b]` colors of alternating stripes.
+    ///
+    /// Returns an `Error` if `height` is 0.
+    fn fill_horizontal_stripes(
+        &mut self,
+        height: usize,
+        color_a: Color,
+        color_b: Color,
+    ) -> Result<(), RgbBufferError> {
+        if height == 0 {
+            return Err(RgbBufferError::InvalidSize { x: X, y: height });
+        }
+        for (y, cols) in self.pixels_mut().iter_mut().enumerate() {
+            cols.fill(if (y / height).is_multiple_of(2) {
+                color_a
+            } else {
+                color_b
+            });
+        }
+        Ok(())
+    }
+
+    /// Tile a sprite across a rectangle, e.g. to draw one layer of a tile map within a viewport.
+    ///
+    /// - `rect` is the rectangle. The top-left pixel of the first tile is at the top-left of `rect`. `rect` is clipped to the buffer.
+    /// - `tile_width` is the width of the tile.
+    /// - `tile` is the tile's `[0, r, g, b]` colors, row by row.
+    ///
+    /// Returns an `Error` if `tile` is empty or its length isn't a multiple of `tile_width`.
+    fn tile_blit(
+        &mut self,
+        rect: Rect,
+        tile_width: usize,
+        tile: &[Color],
+    ) -> Result<(), RgbBufferError> {
+        if tile.is_empty() || !tile.len().is_multiple_of(tile_width) {
+            return Err(RgbBufferError::InvalidSize {
+                x: tile_width,
+                y: tile.len(),
+            });
+        }
+        let tile_rows = tile.chunks_exact(tile_width).collect::<Vec<&[Color]>>();
+        let clipped = rect.clamp_to(X, Y);
+        for (y, cols) in self.pixels_mut()[clipped.y..clipped.y + clipped.h]
+            .iter_mut()
+            .enumerate()
+        {
+            let tile_row = tile_rows[y % tile_rows.len()];
+            cols[clipped.x..clipped.x + clipped.w]
+                .chunks_mut(tile_width)
+                .for_each(|chunk| chunk.copy_from_slice(&tile_row[..chunk.len()]));
+        }
+        Ok(())
+    }
+
+    /// Copy every pixel of `other` into this buffer.
+    /// Both buffers have the same size, so this is a single copy. This is useful for flushing a persistent `OwnedRgbBuffer` to an `RgbBuffer` each frame.
+    fn copy_from(&mut self, other: &impl Canvas<X, Y>) {
+        self.pixels_mut().copy_from_slice(other.pixels());
+    }
+
+    /// Alpha-blend every pixel of `other` over this buffer, e.g. to crossfade between two rendered scenes.
+    ///
+    /// - `other` is the buffer that is blended over this one. Both buffers have the same size.
+    /// - `opacity` is the opacity of `other`. If it's 255, this is the same as `copy_from`. If it's 0, this does nothing.
+    fn blend_buffer(&mut self, other: &impl Canvas<X, Y>, opacity: u8) {
+        match opacity {
+            0 => (),
+            255 => self.copy_from(other),
+            _ => self
+                .pixels_mut()
+                .iter_mut()
+                .flatten()
+                .zip(other.pixels().iter().flatten())
+                .for_each(|(dst, src)| *dst = blend(*dst, *src, opacity)),
+        }
+    }
+
+    /// Copy a `CX` by `CY` region of this buffer into `dst`.
+    ///
+    /// - `x` and `y` are the coordinates of the region's top-left pixel.
+    /// - `dst` is the destination buffer. Its size is the size of the region.
+    ///
+    /// Returns an `Error` if the region isn't entirely inside this buffer.
+    fn crop_into<const CX: usize, const CY: usize>(
+        &self,
+        x: usize,
+        y: usize,
+        dst: &mut impl Canvas<CX, CY>,
+    ) -> Result<(), RgbBufferError> {
+        if !self.contains_rect(Rect::new(x, y, CX, CY)) {
+            return Err(RgbBufferError::InvalidPosition { x, y });
+        }
+        dst.pixels_mut()
+            .iter_mut()
+            .zip(&self.pixels()[y..y + CY])
+            .for_each(|(dst_cols, cols)| dst_cols.copy_from_slice(&cols[x..x + CX]));
+        Ok(())
+    }
+
+    /// Copy a sprite into the buffer, but only the pixels where `mask` is set.
+    ///
+    /// - `x` and `y` are the coordinates of the sprite's top-left pixel.
+    /// - `sprite_width` is the width of the sprite.
+    /// - `sprite` is the sprite's `[0, r, g, b]` colors, row by row.
+    /// - `mask` is the mask. It must be the same size as the sprite.
+    ///
+    /// Returns an `Error` if `sprite_width` isn't `MX` or `sprite.len()` isn't `MX * MY`.
+    /// Pixels that are out of bounds aren't copied.
+    fn blit_masked<const MX: usize, const MY: usize>(
+        &mut self,
+        x: usize,
+        y: usize,
+        sprite_width: usize,
+        sprite: &[Color],
+        mask: &Mask<MX, MY>,
+    ) -> Result<(), RgbBufferError> {
+        if sprite_width != MX || sprite.len() != MX * MY {
+            return Err(RgbBufferError::InvalidSize {
+                x: sprite_width,
+                y: sprite.len(),
+            });
+        }
+        let rows = self.pixels_mut().iter_mut().skip(y);
+        for (sy, (cols, sprite_row)) in rows.zip(sprite.chunks_exact(MX)).enumerate() {
+            let dst = cols.iter_mut().skip(x);
+            for (sx, (c, src)) in dst.zip(sprite_row).enumerate() {
+                if mask.get(sx, sy) {
+                    *c = *src;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw a translucent sprite over a checkerboard, the usual way to preview transparent images.
+    ///
+    /// - `x` and `y` are the coordinates of the sprite's top-left pixel.
+    /// - `sprite_width` is the width of the sprite.
+    /// - `sprite` is the sprite's `[0, r, g, b]` colors, row by row.
+    /// - `alpha` is the opacity of each pixel of the sprite.
+    /// - `cell` is the width and height of each square of the checkerboard.
+    ///
+    /// The checkerboard is white and light gray, and it starts at the sprite's top-left pixel.
+    ///
+    /// Returns an `Error` if `sprite` is empty, the length of `sprite` isn't a multiple of `sprite_width`, the lengths of `sprite` and `alpha` are different, or `cell` is 0.
+    /// Pixels that are out of bounds aren't drawn.
+    fn blit_over_checker(
+        &mut self,
+        x: usize,
+        y: usize,
+        sprite_width: usize,
+        sprite: &[Color],
+        alpha: &[u8],
+        cell: usize,
+    ) -> Result<(), RgbBufferError> {
+        if sprite.is_empty() || !sprite.len().is_multiple_of(sprite_width) {
+            return Err(RgbBufferError::InvalidSize {
+                x: sprite_width,
+                y: sprite.len(),
+            });
+        }
+        if alpha.len() != sprite.len() {
+            return Err(RgbBufferError::InvalidSize {
+                x: sprite_width,
+                y: alpha.len(),
+            });
+        }
+        if cell == 0 {
+            return Err(RgbBufferError::InvalidSize { x: cell, y: cell });
+        }
+        let checker = [WHITE, rgb(204, 204, 204)];
+        let rows = self.pixels_mut().iter_mut().skip(y);
+        let sprite_rows = sprite
+            .chunks_exact(sprite_width)
+            .zip(alpha.chunks_exact(sprite_width));
+        for (sy, (cols, (sprite_row, alpha_row))) in rows.zip(sprite_rows).enumerate() {
+            let dst = cols.iter_mut().skip(x);
+            for (sx, (c, (src, a))) in dst.zip(sprite_row.iter().zip(alpha_row)).enumerate() {
+                let background = checker[(sx / cell + sy / cell) % 2];
+                *c = blend(background, *src, *a);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rotate a sprite around its center and draw it, skipping pixels that are exactly `key`.
+    ///
+    /// - `cx` and `cy` are the coordinates of the sprite's center. If `angle_rad` is 0, the sprite's top-left pixel is drawn at `(cx - sprite_width / 2, cy - sprite_height / 2)`.
+    /// - `sprite_width` is the width of the sprite.
+    /// - `sprite` is the sprite's `[0, r, g, b]` colors, row by row.
+    /// - `angle_rad` is the angle in radians. Positive angles rotate clockwise, because y increases downwards.
+    /// - `key` is the transparent color.
+    ///
+    /// Each pixel of the buffer near the sprite is rotated back into the sprite and set to the nearest pixel of the sprite, so the rotated sprite doesn't have holes.
+    ///
+    /// Returns an `Error` if `sprite` is empty or its length isn't a multiple of `sprite_width`.
+    /// Pixels that are out of bounds aren't drawn.
+    fn blit_rotated(
+        &mut self,
+        cx: usize,
+        cy: usize,
+        sprite_width: usize,
+        sprite: &[Color],
+        angle_rad: f32,
+        key: Color,
+    ) -> Result<(), RgbBufferError> {
+        if sprite.is_empty() || !sprite.len().is_multiple_of(sprite_width) {
+            return Err(RgbBufferError::InvalidSize {
+                x: sprite_width,
+                y: sprite.len(),
+            });
+        }
+        let (sw, sh) = (sprite_width, sprite.len() / sprite_width);
+        // f32 can't represent e.g. `cos(PI / 2)` exactly, so snap tiny values to 0 so that right angles are exact.
+        let snap = |v: f32| if v.abs() < 1e-6 { 0.0 } else { v };
+        let (sin, cos) = (snap(angle_rad.sin()), snap(angle_rad.cos()));
+        // The center of the sprite in the buffer's continuous coordinates.
+        let center_x = cx as f32 + (sw % 2) as f32 / 2.0;
+        let center_y = cy as f32 + (sh % 2) as f32 / 2.0;
+        // Only visit pixels that are within the sprite's rotated bounding circle.
+        let radius = ((sw * sw + sh * sh) as f32).sqrt() / 2.0 + 1.0;
+        let x0 = (center_x - radius).floor().max(0.0) as usize;
+        let y0 = (center_y - radius).floor().max(0.0) as usize;
+        let x1 = ((center_x + radius).ceil() as usize).min(X);
+        let y1 = ((center_y + radius).ceil() as usize).min(Y);
+        if x0 >= x1 || y0 >= y1 {
+            return Ok(());
+        }
+        for (y, cols) in self.pixels_mut()[y0..y1].iter_mut().enumerate() {
+            let oy = (y0 + y) as f32 + 0.5 - center_y;
+            for (x, c) in cols[x0..x1].iter_mut().enumerate() {
+                let ox = (x0 + x) as f32 + 0.5 - center_x;
+                // Rotate the center of this pixel back into the sprite.
+                let u = (cos * ox + sin * oy + sw as f32 / 2.0).floor();
+                let v = (cos * oy - sin * ox + sh as f32 / 2.0).floor();
+                if u >= 0.0 && v >= 0.0 && (u as usize) < sw && (v as usize) < sh {
+                    let src = sprite[v as usize * sw + u as usize];
+                    if src != key {
+                        *c = src;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy `count` rows starting at row `src_y` to row `dst_y`.
+    ///
+    /// This is a single memmove, so the source and destination rows can overlap.
+    ///
+    /// Returns an `Error` if any of the source or destination rows are out of bounds.
+    fn copy_rows(
+        &mut self,
+        src_y: usize,
+        dst_y: usize,
+        count: usize,
+    ) -> Result<(), RgbBufferError> {
+        for y in [src_y, dst_y] {
+            if !self.contains_rect(Rect::new(0, y, X, count)) {
+                return Err(RgbBufferError::InvalidPosition { x: 0, y });
+            }
+        }
+        self.pixels_mut().copy_within(src_y..src_y + count, dst_y);
+        Ok(())
+    }
+
+    /// Copy row `src_y` to row `dst_y`, e.g. to leave a motion trail. This is the same as `copy_rows` with a `count` of 1.
+    ///
+    /// Returns an `Error` if either row is out of bounds.
+    fn copy_row(&mut self, src_y: usize, dst_y: usize) -> Result<(), RgbBufferError> {
+        self.copy_rows(src_y, dst_y, 1)
+    }
+
+    /// Write a vertical strip of colors down column `x`, starting at the top row.
+    ///
+    /// - `x` is the column.
+    /// - `colors` are the `[0, r, g, b]` colors, from top to bottom.
+    ///
+    /// Returns an `Error` if `x` is out of bounds or if there are more than `Y` colors.
+    fn write_column(&mut self, x: usize, colors: &[Color]) -> Result<(), RgbBufferError> {
+        if x >= X {
+            Err(RgbBufferError::InvalidPosition { x, y: 0 })
+        } else if colors.len() > Y {
+            Err(RgbBufferError::InvalidSize {
+                x: 1,
+                y: colors.len(),
+            })
+        } else {
+            self.pixels_mut()
+                .iter_mut()
+                .zip(colors)
+                .for_each(|(cols, c)| cols[x] = *c);
+            Ok(())
+        }
+    }
+
+    /// Shrink this buffer to half its size and copy it into `dst`. Each 2x2 block of pixels is averaged into one pixel.
+    ///
+    /// If `X` or `Y` is odd, the last column or row is ignored.
+    ///
+    /// Returns an `Error` if `HX` isn't `X / 2` or `HY` isn't `Y / 2`.
+    fn downsample_2x_into<const HX: usize, const HY: usize>(
+        &self,
+        dst: &mut impl Canvas<HX, HY>,
+    ) -> Result<(), RgbBufferError> {
+        if HX != X / 2 || HY != Y / 2 {
+            return Err(RgbBufferError::InvalidSize { x: HX, y: HY });
+        }
+        for (dst_cols, rows) in dst
+            .pixels_mut()
+            .iter_mut()
+            .zip(self.pixels().chunks_exact(2))
+        {
+            for (x, c) in dst_cols.iter_mut().enumerate() {
+                let block = [
+                    rows[0][2 * x],
+                    rows[0][2 * x + 1],
+                    rows[1][2 * x],
+                    rows[1][2 * x + 1],
+                ];
+                let average =
+                    |i: usize| ((block.iter().map(|b| b[i] as u16).sum::<u16>() + 2) / 4) as u8;
+                *c = [0, average(1), average(2), average(3)];
+            }
+        }
+        Ok(())
+    }
+
+    /// Shrink this buffer by an integer factor and copy it into `dst`. Each `factor` by `factor` block of pixels is averaged into one pixel.
+    ///
+    /// - `factor` is the scale factor. For example, if it's 4, `dst` is a quarter of the width and height of this buffer.
+    /// - `dst` is the destination buffer.
+    ///
+    /// If `X` or `Y` isn't a multiple of `factor`, the remaining columns or rows are ignored.
+    ///
+    /// Returns an `Error` if `factor` is 0, or if `DX` isn't `X / factor` or `DY` isn't `Y / factor`.
+    fn downscale_into<const DX: usize, const DY: usize>(
+        &self,
+        factor: usize,
+        dst: &mut impl Canvas<DX, DY>,
+    ) -> Result<(), RgbBufferError> {
+        if factor == 0 || DX != X / factor || DY != Y / factor {
+            return Err(RgbBufferError::InvalidSize { x: DX, y: DY });
+        }
+        let n = (factor * factor) as u64;
+        for (dst_cols, rows) in dst
+            .pixels_mut()
+            .iter_mut()
+            .zip(self.pixels().chunks_exact(factor))
+        {
+            for (x, c) in dst_cols.iter_mut().enumerate() {
+                let mut sums = [0u64; 3];
+                for cols in rows {
+                    for block in cols[x * factor..(x + 1) * factor].iter() {
+                        sums.iter_mut()
+                            .zip(&block[1..])
+                            .for_each(|(sum, v)| *sum += *v as u64);
+                    }
+                }
+                let [r, g, b] = sums.map(|sum| ((sum + n / 2) / n) as u8);
+                *c = [0, r, g, b];
+            }
+        }
+        Ok(())
+    }
+
+    /// Alpha-blend a color over the pixel at `(x, y)`.
+    ///
+    /// - `x` and `y` are the coordinates of the pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `alpha` is the opacity of `color`.
+    ///
+    /// If the position is out of bounds, this does nothing.
+    fn blend_pixel(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        if self.contains(x, y) {
+            let pixel = &mut self.pixels_mut()[y][x];
+            *pixel = blend(*pixel, color, alpha);
+        }
+    }
+
+    /// Alpha-blend a color over every pixel in a rectangle, e.g. to dim the area behind a dialog.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `alpha` is the opacity of `color`.
+    ///
+    /// The rectangle is clipped to the buffer.
+    fn blend_rectangle(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color, alpha: u8) {
+        let rect = Rect::new(x, y, w, h).clamp_to(X, Y);
+        self.pixels_mut()[rect.y..rect.y + rect.h]
+            .iter_mut()
+            .for_each(|cols| {
+                cols[rect.x..rect.x + rect.w]
+                    .iter_mut()
+                    .for_each(|c| *c = blend(*c, color, alpha))
+            });
+    }
+
+    /// Additively blend a color into the pixel at `(x, y)`, e.g. to draw glowing particles that get brighter where they overlap.
+    ///
+    /// - `x` and `y` are the coordinates of the pixel.
+    /// - `color` is the `[0, r, g, b]` color. Each channel is added to the pixel's channel, saturating at 255.
+    ///
+    /// If the position is out of bounds, this does nothing.
+    fn add_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if self.contains(x, y) {
+            let pixel = &mut self.pixels_mut()[y][x];
+            *pixel = add(*pixel, color);
+        }
+    }
+
+    /// Additively blend a color into every pixel in a rectangle.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    /// - `color` is the `[0, r, g, b]` color. Each channel is added to the pixels' channels, saturating at 255.
+    ///
+    /// The rectangle is clipped to the buffer.
+    fn add_rectangle(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color) {
+        let rect = Rect::new(x, y, w, h).clamp_to(X, Y);
+        self.pixels_mut()[rect.y..rect.y + rect.h]
+            .iter_mut()
+            .for_each(|cols| {
+                cols[rect.x..rect.x + rect.w]
+                    .iter_mut()
+                    .for_each(|c| *c = add(*c, color))
+            });
+    }
+
+    /// Draw the 1-pixel outline of a rectangle.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Panics if the top-left or bottom-right positions are out of bounds.
+    fn draw_rectangle(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color) {
+        self.draw_rect(Rect::new(x, y, w, h), color);
+    }
+
+    /// Draw the 1-pixel outline of a rectangle.
+    ///
+    /// - `rect` is the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Panics if the top-left or bottom-right positions are out of bounds.
+    fn draw_rect(&mut self, rect: Rect, color: Color) {
+        if rect.is_empty() {
+            return;
+        }
+        let x1 = rect.x + rect.w - 1;
+        let y1 = rect.y + rect.h - 1;
+        // Top and bottom edges.
+        let pixels = self.pixels_mut();
+        pixels[rect.y][rect.x..=x1].fill(color);
+        pixels[y1][rect.x..=x1].fill(color);
+        // Left and right edges.
+        pixels[rect.y..=y1].iter_mut().for_each(|cols| {
+            cols[rect.x] = color;
+            cols[x1] = color;
+        });
+    }
+
+    /// Draw the 1-pixel dashed outline of a rectangle, e.g. a "marching ants" selection marquee.
+    ///
+    /// - `rect` is the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `dash` is the length of each dash in pixels. If it's 0, this does nothing.
+    /// - `gap` is the length of each gap in pixels. If it's 0, this is the same as `draw_rect`.
+    /// - `phase` is how many pixels into the dash pattern the outline starts. Increment it every frame to animate the outline.
+    ///
+    /// The dash pattern starts at the top-left corner and continues clockwise around the corners, rather than restarting on each edge.
+    /// Pixels that are out of bounds aren't drawn, but they still advance the dash pattern.
+    fn draw_rect_dashed(
+        &mut self,
+        rect: Rect,
+        color: Color,
+        dash: usize,
+        gap: usize,
+        phase: usize,
+    ) {
+        if dash == 0 || rect.is_empty() {
+            return;
+        }
+        let (x0, y0) = (rect.x, rect.y);
+        let x1 = x0.saturating_add(rect.w - 1);
+        let y1 = y0.saturating_add(rect.h - 1);
+        // Walk clockwise around the edges. Each pixel is visited once, even if the rectangle is 1 pixel wide or tall.
+        let top = (x0..=x1).map(|x| (x, y0));
+        let right = (y0 + 1..=y1).map(|y| (x1, y));
+        let bottom = (if y1 > y0 { x0..x1 } else { x0..x0 })
+            .rev()
+            .map(|x| (x, y1));
+        let left = (if x1 > x0 { y0 + 1..y1 } else { y0..y0 })
+            .rev()
+            .map(|y| (x0, y));
+        let period = dash.saturating_add(gap);
+        // The position in the dash pattern.
+        let mut i = phase % period;
+        let pixels = self.pixels_mut();
+        for (x, y) in top.chain(right).chain(bottom).chain(left) {
+            if i < dash && x < X && y < Y {
+                pixels[y][x] = color;
+            }
+            i = (i + 1) % period;
+        }
+    }
+
+    /// Fill a frame just inside the edges of a rectangle, e.g. a focus indicator or a panel border. The interior isn't changed.
+    ///
+    /// - `rect` is the outer edge of the frame.
+    /// - `thickness` is the thickness of the frame. If it's at least half of the rectangle's width or height, the whole rectangle is filled.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// The frame is filled as four rectangles. Pixels that are out of bounds aren't drawn.
+    fn fill_border(&mut self, rect: Rect, thickness: usize, color: Color) {
+        let t = thickness.min(rect.w.div_ceil(2)).min(rect.h.div_ceil(2));
+        if t == 0 {
+            return;
+        }
+        let inner_h = rect.h.saturating_sub(2 * t);
+        let bands = [
+            // Top and bottom.
+            Rect::new(rect.x, rect.y, rect.w, t),
+            Rect::new(rect.x, rect.y.saturating_add(rect.h - t), rect.w, t),
+            // Left and right.
+            Rect::new(rect.x, rect.y.saturating_add(t), t, inner_h),
+            Rect::new(
+                rect.x.saturating_add(rect.w - t),
+                rect.y.saturating_add(t),
+                t,
+                inner_h,
+            ),
+        ];
+        let pixels = self.pixels_mut();
+        for band in bands.map(|band| band.clamp_to(X, Y)) {
+            pixels[band.y..band.y + band.h]
+                .iter_mut()
+                .for_each(|cols| cols[band.x..band.x + band.w].fill(color));
+        }
+    }
+
+    /// Draw a horizontal 1-pixel line. This is faster than `draw_line`.
+    ///
+    /// - `x` and `y` are the coordinates of the leftmost pixel.
+    /// - `w` is the length of the line.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_hline(&mut self, x: usize, y: usize, w: usize, color: Color) {
+        if let Some(cols) = self.pixels_mut().get_mut(y) {
+            let x0 = x.min(X);
+            let x1 = x.saturating_add(w).min(X);
+            cols[x0..x1].fill(color);
+        }
+    }
+
+    /// Draw a vertical 1-pixel line. This is faster than `draw_line`.
+    ///
+    /// - `x` and `y` are the coordinates of the topmost pixel.
+    /// - `h` is the length of the line.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_vline(&mut self, x: usize, y: usize, h: usize, color: Color) {
+        if x < X {
+            let y0 = y.min(Y);
+            let y1 = y.saturating_add(h).min(Y);
+            self.pixels_mut()[y0..y1]
+                .iter_mut()
+                .for_each(|cols| cols[x] = color);
+        }
+    }
+
+    /// Draw a `+` centered at `(x, y)`. This is the same as `draw_marker` with `MarkerShape::Plus`.
+    ///
+    /// - `x` and `y` are the coordinates of the center.
+    /// - `size` is the length of each arm, not including the center. If it's 0, this sets a single pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_crosshair(&mut self, x: usize, y: usize, size: usize, color: Color) {
+        self.draw_marker(x, y, MarkerShape::Plus, size, color);
+    }
+
+    /// Draw a small marker centered at `(x, y)`, e.g. a point in a scatter plot.
+    ///
+    /// - `x` and `y` are the coordinates of the center.
+    /// - `shape` is the shape of the marker.
+    /// - `size` is the distance from the center to the edge of the marker, so the marker is `2 * size + 1` pixels wide. If it's 0, this sets a single pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_marker(&mut self, x: usize, y: usize, shape: MarkerShape, size: usize, color: Color) {
+        // A circle is an ellipse. Every other shape is made of lines.
+        if shape == MarkerShape::Circle {
+            self.draw_ellipse(x, y, size, size, color);
+            return;
+        }
+        let pixels = self.pixels_mut();
+        let (x, y, s) = (x as isize, y as isize, size as isize);
+        let mut line = |x0: isize, y0: isize, x1: isize, y1: isize| {
+            for (x, y) in line_points(x0, y0, x1, y1) {
+                plot::<X, Y>(pixels, x, y, color);
+            }
+        };
+        match shape {
+            MarkerShape::Cross => {
+                line(x - s, y - s, x + s, y + s);
+                line(x - s, y + s, x + s, y - s);
+            }
+            MarkerShape::Plus => {
+                line(x - s, y, x + s, y);
+                line(x, y - s, x, y + s);
+            }
+            MarkerShape::Square => {
+                line(x - s, y - s, x + s, y - s);
+                line(x - s, y + s, x + s, y + s);
+                line(x - s, y - s, x - s, y + s);
+                line(x + s, y - s, x + s, y + s);
+            }
+            MarkerShape::Diamond => {
+                line(x, y - s, x + s, y);
+                line(x + s, y, x, y + s);
+                line(x, y + s, x - s, y);
+                line(x - s, y, x, y - s);
+            }
+            MarkerShape::Circle => {}
+        }
+    }
+
+    /// Draw a grid of 1-pixel lines across the entire buffer, starting at the top-left corner.
+    ///
+    /// - `spacing` is the distance between lines.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Returns an `Error` if `spacing` is 0.
+    fn draw_grid(&mut self, spacing: usize, color: Color) -> Result<(), RgbBufferError> {
+        self.draw_grid_offset(spacing, 0, 0, color)
+    }
+
+    /// Draw a grid of 1-pixel lines across the entire buffer, shifted to align with a scrolled viewport.
+    ///
+    /// - `spacing` is the distance between lines.
+    /// - `x_offset` and `y_offset` are the viewport's scroll position. Lines are drawn at each `x` where `(x + x_offset) % spacing == 0`, and likewise for `y`.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Returns an `Error` if `spacing` is 0.
+    fn draw_grid_offset(
+        &mut self,
+        spacing: usize,
+        x_offset: usize,
+        y_offset: usize,
+        color: Color,
+    ) -> Result<(), RgbBufferError> {
+        if spacing == 0 {
+            return Err(RgbBufferError::InvalidSize {
+                x: spacing,
+                y: spacing,
+            });
+        }
+        let first = |offset: usize| (spacing - offset % spacing) % spacing;
+        for x in (first(x_offset)..X).step_by(spacing) {
+            self.draw_vline(x, 0, Y, color);
+        }
+        for y in (first(y_offset)..Y).step_by(spacing) {
+            self.draw_hline(0, y, X, color);
+        }
+        Ok(())
+    }
+
+    /// Draw a 1-pixel line using Bresenham's line algorithm.
+    ///
+    /// - `x0` and `y0` are the coordinates of the start of the line.
+    /// - `x1` and `y1` are the coordinates of the end of the line.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_line(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, color: Color) {
+        // Clip the line first so that the pixels don't need to be bounds-checked individually.
+        let pixels = self.pixels_mut();
+        for (x, y) in line_points(x0 as isize, y0 as isize, x1 as isize, y1 as isize).clip(X, Y) {
+            pixels[y as usize][x as usize] = color;
+        }
+    }
+
+    /// Draw a 1-pixel dashed line using Bresenham's line algorithm.
+    ///
+    /// - `x0` and `y0` are the coordinates of the start of the line.
+    /// - `x1` and `y1` are the coordinates of the end of the line.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `dash` is the length of each dash in pixels. If it's 0, this does nothing.
+    /// - `gap` is the length of each gap in pixels. If it's 0, this is the same as `draw_line`.
+    /// - `phase` is how many pixels into the dash pattern the line starts. Increment it every frame for a "marching ants" animation.
+    ///
+    /// Pixels that are out of bounds aren't drawn, but they still advance the dash pattern.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_line_dashed(
+        &mut self,
+        x0: usize,
+        y0: usize,
+        x1: usize,
+        y1: usize,
+        color: Color,
+        dash: usize,
+        gap: usize,
+        phase: usize,
+    ) {
+        if dash == 0 {
+            return;
+        }
+        let period = dash.saturating_add(gap);
+        let points = line_points(x0 as isize, y0 as isize, x1 as isize, y1 as isize).clip(X, Y);
+        // The position in the dash pattern. The points that were clipped still advance it.
+        let mut i = ((phase as u128 + points.position()) % period as u128) as usize;
+        let pixels = self.pixels_mut();
+        for (x, y) in points {
+            if i < dash {
+                pixels[y as usize][x as usize] = color;
+            }
+            i = (i + 1) % period;
+        }
+    }
+
+    /// Draw a line that is `width` pixels wide, with round ends.
+    ///
+    /// - `x0` and `y0` are the coordinates of the start of the line.
+    /// - `x1` and `y1` are the coordinates of the end of the line.
+    /// - `width` is the width of the line. If it's 0, this does nothing. If it's 1, this is the same as `draw_line`.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Each point of the line is stamped with `fill_circle`, so even widths are rounded up to the next odd width.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_line_thick(
+        &mut self,
+        x0: usize,
+        y0: usize,
+        x1: usize,
+        y1: usize,
+        width: usize,
+        color: Color,
+    ) {
+        if width == 0 || X == 0 || Y == 0 {
+            return;
+        }
+        let radius = width / 2;
+        // Stamps that are centered up to `radius` pixels beyond the right or bottom edges can still be partially visible.
+        // Clip by step, like `draw_line`, so that a width of 1 draws the same pixels.
+        let w = X.saturating_add(radius).min(isize::MAX as usize);
+        let h = Y.saturating_add(radius).min(isize::MAX as usize);
+        for (x, y) in line_points(x0 as isize, y0 as isize, x1 as isize, y1 as isize).clip(w, h) {
+            self.fill_circle(x as usize, y as usize, radius, color);
+        }
+    }
+
+    /// Clip a line to the buffer using the Cohen-Sutherland algorithm.
+    ///
+    /// - `x0` and `y0` are the coordinates of the start of the line.
+    /// - `x1` and `y1` are the coordinates of the end of the line.
+    ///
+    /// Returns the `(x0, y0, x1, y1)` coordinates of the part of the line that is inside the buffer, or `None` if the line is entirely outside of the buffer.
+    /// Clipped endpoints are rounded to the nearest pixel.
+    fn clip_line(
+        &self,
+        x0: usize,
+        y0: usize,
+        x1: usize,
+        y1: usize,
+    ) -> Option<(usize, usize, usize, usize)> {
+        if X == 0 || Y == 0 {
+            return None;
+        }
+        clip_line(
+            x0 as isize,
+            y0 as isize,
+            x1 as isize,
+            y1 as isize,
+            X as isize - 1,
+            Y as isize - 1,
+        )
+        .map(|(x0, y0, x1, y1)| (x0 as usize, y0 as usize, x1 as usize, y1 as usize))
+    }
+
+    /// Draw a quadratic Bézier curve.
+    ///
+    /// - `p0` is the start of the curve.
+    /// - `p1` is the control point.
+    /// - `p2` is the end of the curve.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// The curve is subdivided until each piece is within a quarter of a pixel of a straight line, and then each piece is drawn like `draw_line`.
+    /// Points can be out of bounds. Pixels that are out of bounds aren't drawn.
+    fn draw_bezier_quadratic(
+        &mut self,
+        p0: (f32, f32),
+        p1: (f32, f32),
+        p2: (f32, f32),
+        color: Color,
+    ) {
+        let pixels = self.pixels_mut();
+        bezier_quadratic_segments(p0, p1, p2, 0, &mut |a, b| {
+            plot_segment::<X, Y>(pixels, a, b, color)
+        });
+    }
+
+    /// Draw a Catmull-Rom spline, which is a smooth curve that passes through each point.
+    ///
+    /// - `points` are the points. If there are fewer than 2 points, this does nothing.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `segments_per_span` is the number of straight lines that are drawn between each pair of consecutive points. If it's 0, this draws straight lines between the points.
+    ///
+    /// The first and last points are duplicated, so the curve starts and ends at them.
+    /// Points can be out of bounds. Pixels that are out of bounds aren't drawn.
+    fn draw_catmull_rom(&mut self, points: &[(f32, f32)], color: Color, segments_per_span: usize) {
+        if points.len() < 2 {
+            return;
+        }
+        let segments = segments_per_span.max(1);
+        let pixels = self.pixels_mut();
+        let last = points.len() - 1;
+        for i in 0..last {
+            let p0 = points[i.saturating_sub(1)];
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let p3 = points[(i + 2).min(last)];
+            // Evaluate the uniform Catmull-Rom polynomial for each coordinate.
+            let eval = |a: f32, b: f32, c: f32, d: f32, t: f32| {
+                0.5 * (2.0 * b
+                    + (c - a) * t
+                    + (2.0 * a - 5.0 * b + 4.0 * c - d) * t * t
+                    + (3.0 * b - a - 3.0 * c + d) * t * t * t)
+            };
+            let mut a = p1;
+            for s in 1..=segments {
+                let t = s as f32 / segments as f32;
+                let b = (
+                    eval(p0.0, p1.0, p2.0, p3.0, t),
+                    eval(p0.1, p1.1, p2.1, p3.1, t),
+                );
+                plot_segment::<X, Y>(pixels, a, b, color);
+                a = b;
+            }
+        }
+    }
+
+    /// Fill a circle.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `radius` is the radius. If it's 0, this sets a single pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn fill_circle(&mut self, cx: usize, cy: usize, radius: usize, color: Color) {
+        let pixels = self.pixels_mut();
+        let (cx, cy, r) = (cx as isize, cy as isize, circle_radius(radius));
+        for dy in circle_rows(cy, r, Y) {
+            let dx = circle_half_width(r, dy);
+            fill_span::<X, Y>(
+                pixels,
+                cy + dy,
+                cx.saturating_sub(dx),
+                cx.saturating_add(dx),
+                color,
+            );
+        }
+    }
+
+    /// Fill an antialiased circle, e.g. a smooth dot or marker.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center. They can be fractional, so that an animated circle moves smoothly.
+    /// - `radius` is the radius. If it isn't positive, this does nothing.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Each pixel is centered on its integer coordinates. Pixels within the circle are filled with `color`.
+    /// Pixels on the edge are alpha-blended with `color`, based on their distance to the edge.
+    /// Pixels that are out of bounds aren't drawn.
+    fn fill_circle_aa(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        if radius.is_nan() || radius <= 0.0 {
+            return;
+        }
+        // The bounding box, including the edge. Casts saturate, so this is clipped to the buffer.
+        let r = radius + 0.5;
+        let x0 = (cx - r).floor() as usize;
+        let x1 = ((cx + r).ceil() as usize).min(X.saturating_sub(1));
+        let y0 = (cy - r).floor() as usize;
+        let y1 = ((cy + r).ceil() as usize).min(Y.saturating_sub(1));
+        for (y, cols) in self
+            .pixels_mut()
+            .iter_mut()
+            .enumerate()
+            .take(y1 + 1)
+            .skip(y0)
+        {
+            let dy = y as f32 - cy;
+            for (x, c) in cols.iter_mut().enumerate().take(x1 + 1).skip(x0) {
+                let dx = x as f32 - cx;
+                // The fraction of the pixel that is inside the circle.
+                let coverage = (r - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0);
+                *c = blend(*c, color, (coverage * 255.0).round() as u8);
+            }
+        }
+    }
+
+    /// Fill a ring (annulus), e.g. a progress ring or a donut chart.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `inner_radius` is the radius of the hole. The pixels that `fill_circle` would fill with this radius aren't drawn.
+    /// - `outer_radius` is the outer radius. The pixels that `fill_circle` would fill with this radius are drawn.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// If `inner_radius >= outer_radius`, this does nothing.
+    /// Pixels that are out of bounds aren't drawn.
+    fn fill_ring(
+        &mut self,
+        cx: usize,
+        cy: usize,
+        inner_radius: usize,
+        outer_radius: usize,
+        color: Color,
+    ) {
+        if inner_radius >= outer_radius {
+            return;
+        }
+        let pixels = self.pixels_mut();
+        let (cx, cy) = (cx as isize, cy as isize);
+        let (ri, ro) = (circle_radius(inner_radius), circle_radius(outer_radius));
+        for dy in circle_rows(cy, ro, Y) {
+            let outer = circle_half_width(ro, dy);
+            let (x0, x1) = (cx.saturating_sub(outer), cx.saturating_add(outer));
+            if dy.abs() > ri {
+                fill_span::<X, Y>(pixels, cy + dy, x0, x1, color);
+            } else {
+                // Fill the spans on either side of the hole.
+                let inner = circle_half_width(ri, dy).saturating_add(1);
+                fill_span::<X, Y>(pixels, cy + dy, x0, cx.saturating_sub(inner), color);
+                fill_span::<X, Y>(pixels, cy + dy, cx.saturating_add(inner), x1, color);
+            }
+        }
+    }
+
+    /// Draw the outline of a circle between two angles using the midpoint circle algorithm.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `radius` is the radius. If it's 0, this sets a single pixel.
+    /// - `start_deg` and `end_deg` are the angles in degrees. 0 points right and angles increase clockwise, because y increases downwards.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// The arc always goes clockwise from `start_deg` to `end_deg`, wrapping past 360 if needed.
+    /// For example, `start_deg = 270.0` and `end_deg = 90.0` draws the right half of the circle.
+    /// If `end_deg - start_deg` is at least 360, this draws the whole circle.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_arc(
+        &mut self,
+        cx: usize,
+        cy: usize,
+        radius: usize,
+        start_deg: f32,
+        end_deg: f32,
+        color: Color,
+    ) {
+        let pixels = self.pixels_mut();
+        let (cx, cy, r) = (cx as isize, cy as isize, radius as isize);
+        if r == 0 {
+            plot::<X, Y>(pixels, cx, cy, color);
+            return;
+        }
+        let full = end_deg - start_deg >= 360.0;
+        let start = start_deg.rem_euclid(360.0);
+        let sweep = (end_deg - start_deg).rem_euclid(360.0);
+        let mut plot_arc = |x: isize, y: isize| {
+            let angle = (y as f32).atan2(x as f32).to_degrees();
+            if full || (angle - start).rem_euclid(360.0) <= sweep {
+                plot::<X, Y>(pixels, cx + x, cy + y, color);
+            }
+        };
+        // Step through one octant and mirror it into the other seven.
+        let mut x = r;
+        let mut y = 0;
+        let mut err = 1 - r;
+        while x >= y {
+            plot_arc(x, y);
+            plot_arc(y, x);
+            plot_arc(-y, x);
+            plot_arc(-x, y);
+            plot_arc(-x, -y);
+            plot_arc(-y, -x);
+            plot_arc(y, -x);
+            plot_arc(x, -y);
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Fill the buffer with a radial gradient.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `radius` is the distance at which the gradient reaches `outer`.
+    /// - `inner` is the `[0, r, g, b]` color at the center.
+    /// - `outer` is the `[0, r, g, b]` color at `radius` and beyond.
+    ///
+    /// The center can be out of bounds.
+    fn fill_gradient_radial(
+        &mut self,
+        cx: usize,
+        cy: usize,
+        radius: usize,
+        inner: Color,
+        outer: Color,
+    ) {
+        let r = radius as f32;
+        let r2 = radius.saturating_mul(radius);
+        for (y, cols) in self.pixels_mut().iter_mut().enumerate() {
+            let dy = y.abs_diff(cy);
+            // This row is entirely outside of the circle.
+            if dy >= radius {
+                cols.fill(outer);
+                continue;
+            }
+            let dy2 = dy.saturating_mul(dy);
+            for (x, c) in cols.iter_mut().enumerate() {
+                let dx = x.abs_diff(cx);
+                // Compare squared distances so that pixels outside of the circle don't need a square root.
+                let d2 = if dx < radius {
+                    dx.saturating_mul(dx).saturating_add(dy2)
+                } else {
+                    r2
+                };
+                *c = if d2 >= r2 {
+                    outer
+                } else {
+                    blend(inner, outer, ((d2 as f32).sqrt() / r * 255.0).round() as u8)
+                };
+            }
+        }
+    }
+
+    /// Fill the buffer with a linear gradient along any direction, e.g. a diagonal background.
+    ///
+    /// - `start` and `end` are the coordinates of the points at which the gradient is `start_color` and `end_color`. They can be out of bounds.
+    /// - `start_color` is the `[0, r, g, b]` color at `start` and before it.
+    /// - `end_color` is the `[0, r, g, b]` color at `end` and beyond it.
+    ///
+    /// Each pixel is projected onto the line from `start` to `end`. Pixels on a line that is perpendicular to it have the same color.
+    /// If `start` and `end` are the same point, this fills the buffer with `start_color`.
+    fn fill_gradient_linear(
+        &mut self,
+        start: (f32, f32),
+        end: (f32, f32),
+        start_color: Color,
+        end_color: Color,
+    ) {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let len2 = dx * dx + dy * dy;
+        if len2 == 0.0 {
+            self.fill(start_color);
+            return;
+        }
+        self.shade(|x, y| {
+            // How far along the line the pixel is, from 0 at `start` to 1 at `end`.
+            let t = ((x as f32 - start.0) * dx + (y as f32 - start.1) * dy) / len2;
+            blend(
+                start_color,
+                end_color,
+                (t.clamp(0.0, 1.0) * 255.0).round() as u8,
+            )
+        });
+    }
+
+    /// Fill a polygon using the even-odd rule. The polygon can be concave or self-intersecting.
+    ///
+    /// - `points` are the coordinates of the vertices. The last vertex connects back to the first. If there are fewer than 3 vertices, this does nothing.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// A pixel is filled if its center is inside the polygon.
+    /// Pixels that are out of bounds aren't drawn.
+    fn fill_polygon(&mut self, points: &[(i32, i32)], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let pixels = self.pixels_mut();
+        let (y_min, y_max) = points.iter().fold((i32::MAX, i32::MIN), |(y0, y1), p| {
+            (y0.min(p.1), y1.max(p.1))
+        });
+        let y0 = (y_min as isize).max(0);
+        let y1 = (y_max as isize).min(Y as isize);
+        let mut xs = Vec::with_capacity(points.len());
+        for y in y0..y1 {
+            // Find where each edge crosses the center of this row.
+            let yc = y as f64 + 0.5;
+            xs.clear();
+            for (i, &(ax, ay)) in points.iter().enumerate() {
+                let (bx, by) = points[(i + 1) % points.len()];
+                let (ay, by) = (ay as f64, by as f64);
+                // Horizontal edges never cross the center of a row.
+                if (ay <= yc && yc < by) || (by <= yc && yc < ay) {
+                    xs.push(ax as f64 + (yc - ay) * (bx as f64 - ax as f64) / (by - ay));
+                }
+            }
+            xs.sort_by(f64::total_cmp);
+            // Fill the pixels whose centers are between each pair of crossings.
+            for pair in xs.chunks_exact(2) {
+                let x0 = (pair[0] - 0.5).ceil() as isize;
+                let x1 = (pair[1] - 0.5).ceil() as isize - 1;
+                fill_span::<X, Y>(pixels, y, x0, x1, color);
+            }
+        }
+    }
+
+    /// Returns the color of the pixel at `(x, y)`, clamped to the edges of the buffer.
+    /// For example, `(-3, 5)` returns the color of the pixel at `(0, 5)`.
+    ///
+    /// Panics if the buffer is empty.
+    fn get_clamped(&self, x: isize, y: isize) -> Color {
+        let x = x.clamp(0, X as isize - 1) as usize;
+        let y = y.clamp(0, Y as isize - 1) as usize;
+        self.pixels()[y][x]
+    }
+
+    /// Returns the color of the pixel nearest to `(x, y)`, clamped to the edges of the buffer.
+    ///
+    /// Panics if the buffer is empty.
+    fn sample_nearest(&self, x: f32, y: f32) -> Color {
+        self.get_clamped(x.round() as isize, y.round() as isize)
+    }
+
+    /// Fill a triangle, interpolating the colors of its vertices across it (Gouraud shading).
+    ///
+    /// - `p0`, `p1`, and `p2` are the vertices. Each is `(x, y, color)`, where `color` is the `[0, r, g, b]` color of the vertex.
+    ///
+    /// A pixel is filled if its center is inside the triangle or on an edge.
+    /// Its color is the average of the vertex colors, weighted by the barycentric coordinates of its center.
+    /// If the vertices are collinear, this does nothing.
+    /// Pixels that are out of bounds aren't drawn.
+    fn fill_triangle_gradient(
+        &mut self,
+        p0: (usize, usize, Color),
+        p1: (usize, usize, Color),
+        p2: (usize, usize, Color),
+    ) {
+        // Double the coordinates so that pixel centers are integers.
+        let v = [p0, p1, p2].map(|(x, y, _)| (2 * x as i64, 2 * y as i64));
+        let colors = [p0.2, p1.2, p2.2];
+        // Twice the signed area of the triangle.
+        let mut area = edge(v[0], v[1], v[2]);
+        if area == 0 {
+            return;
+        }
+        // Wind the triangle so that the area and the weights of points inside it are positive.
+        let sign = area.signum();
+        area *= sign;
+        // Clip the triangle's bounding box to the buffer.
+        let x0 = p0.0.min(p1.0).min(p2.0);
+        let y0 = p0.1.min(p1.1).min(p2.1);
+        if x0 >= X || y0 >= Y {
+            return;
+        }
+        let x1 = p0.0.max(p1.0).max(p2.0).min(X - 1);
+        let y1 = p0.1.max(p1.1).max(p2.1).min(Y - 1);
+        for (y, cols) in self.pixels_mut()[y0..=y1].iter_mut().enumerate() {
+            let y = y0 + y;
+            // The weights of each vertex at the center of the leftmost pixel in this row.
+            let p = (2 * x0 as i64 + 1, 2 * y as i64 + 1);
+            let mut w = [(1, 2), (2, 0), (0, 1)].map(|(a, b)| sign * edge(v[a], v[b], p));
+            // How much each weight changes per pixel.
+            let dw = [(1, 2), (2, 0), (0, 1)].map(|(a, b)| -2 * sign * (v[b].1 - v[a].1));
+            for c in cols[x0..=x1].iter_mut() {
+                if w.iter().all(|w| *w >= 0) {
+                    for i in 1..4 {
+                        let sum = (0..3).map(|j| w[j] * colors[j][i] as i64).sum::<i64>();
+                        c[i] = ((sum + area / 2) / area) as u8;
+                    }
+                    c[0] = 0;
+                }
+                w.iter_mut().zip(dw).for_each(|(w, dw)| *w += dw);
+            }
+        }
+    }
+
+    /// Fill a triangle whose vertices can be out of bounds, e.g. transformed geometry that extends past the edges of the buffer.
+    ///
+    /// - `p0`, `p1`, and `p2` are the `(x, y)` coordinates of the vertices. They can be negative.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// A pixel is filled if its center is inside the triangle or on an edge, like `fill_triangle_gradient`.
+    /// If the vertices are collinear, this does nothing.
+    /// Rows that are out of bounds are skipped, and each row's span is clipped to the buffer.
+    fn fill_triangle_clipped(
+        &mut self,
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        color: Color,
+    ) {
+        // Double the coordinates so that pixel centers are integers.
+        let v = [p0, p1, p2].map(|(x, y)| (2 * x as i64, 2 * y as i64));
+        let area = edge(v[0], v[1], v[2]);
+        if area == 0 {
+            return;
+        }
+        // Wind the triangle so that the weights of points inside it are positive.
+        let sign = area.signum();
+        // Clip the triangle's bounding box to the buffer.
+        let x0 = p0.0.min(p1.0).min(p2.0).max(0) as i64;
+        let y0 = p0.1.min(p1.1).min(p2.1).max(0) as i64;
+        let x1 = (p0.0.max(p1.0).max(p2.0) as i64).min(X as i64 - 1);
+        let y1 = (p0.1.max(p1.1).max(p2.1) as i64).min(Y as i64 - 1);
+        if x0 > x1 || y0 > y1 {
+            return;
+        }
+        // How much each weight changes per pixel.
+        let dw = [(1, 2), (2, 0), (0, 1)].map(|(a, b)| -2 * sign * (v[b].1 - v[a].1));
+        let pixels = self.pixels_mut();
+        for y in y0..=y1 {
+            // The weights of each vertex at the center of the leftmost pixel in this row.
+            let p = (2 * x0 + 1, 2 * y + 1);
+            let mut w = [(1, 2), (2, 0), (0, 1)].map(|(a, b)| sign * edge(v[a], v[b], p));
+            // The triangle is convex, so the pixels inside it are a single span.
+            let mut span: Option<(i64, i64)> = None;
+            for x in x0..=x1 {
+                if w.iter().all(|w| *w >= 0) {
+                    span = Some((span.map_or(x, |(start, _)| start), x));
+                } else if span.is_some() {
+                    break;
+                }
+                w.iter_mut().zip(dw).for_each(|(w, dw)| *w += dw);
+            }
+            if let Some((start, end)) = span {
+                pixels[y as usize][start as usize..=end as usize].fill(color);
+            }
+        }
+    }
+
+    /// Returns the `luminance` of the pixel at `(x, y)`, or `None` if the position is out of bounds.
+    fn luminance_at(&self, x: usize, y: usize) -> Option<u8> {
+        self.pixels()
+            .get(y)
+            .and_then(|cols| cols.get(x))
+            .map(|c| luminance(*c))
+    }
+
+    /// Returns the average color of the pixels in `rect`, rounded to the nearest integer.
+    /// `rect` is clipped to the buffer. If the clipped rectangle is empty, this returns black.
+    fn average_color(&self, rect: Rect) -> Color {
+        let rect = rect.clamp_to(X, Y);
+        if rect.is_empty() {
+            return [0; 4];
+        }
+        let mut sums = [0u64; 3];
+        for cols in self.pixels()[rect.y..rect.y + rect.h].iter() {
+            for c in cols[rect.x..rect.x + rect.w].iter() {
+                sums.iter_mut()
+                    .zip(&c[1..])
+                    .for_each(|(sum, v)| *sum += *v as u64);
+            }
+        }
+        let n = (rect.w * rect.h) as u64;
+        let [r, g, b] = sums.map(|sum| ((sum + n / 2) / n) as u8);
+        [0, r, g, b]
+    }
+
+    /// Returns a text preview of the buffer that can be printed to a terminal.
+    ///
+    /// - `cols` and `rows` are the number of characters per line and the number of lines.
+    ///
+    /// The buffer is divided into `cols` by `rows` cells. The `average_color` of each cell is converted to a character from the ramp `" .:-=+*#%@"` by its `luminance`, from dark to bright.
+    /// Lines are separated by `'\n'`. If `cols` or `rows` is 0, or the buffer is empty, this returns an empty string.
+    fn debug_ascii(&self, cols: usize, rows: usize) -> String {
+        const RAMP: &[u8] = b" .:-=+*#%@";
+        if cols == 0 || rows == 0 || X == 0 || Y == 0 {
+            return String::new();
+        }
+        let mut lines = Vec::with_capacity(rows);
+        for row in 0..rows {
+            let y = row * Y / rows;
+            // Each cell is at least one pixel.
+            let h = ((row + 1) * Y / rows).max(y + 1) - y;
+            let line = (0..cols)
+                .map(|col| {
+                    let x = col * X / cols;
+                    let w = ((col + 1) * X / cols).max(x + 1) - x;
+                    let l = luminance(self.average_color(Rect::new(x, y, w, h))) as usize;
+                    RAMP[l * RAMP.len() / 256] as char
+                })
+                .collect::<String>();
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    /// Returns the number of pixels that are exactly `color`.
+    fn count_color(&self, color: Color) -> usize {
+        self.pixels()
+            .iter()
+            .map(|cols| cols.iter().filter(|c| **c == color).count())
+            .sum()
+    }
+
+    /// Returns the coordinates of the first pixel, in row-major order, for which `predicate` returns true, or `None` if there is no such pixel.
+    fn find_pixel(&self, predicate: impl Fn(Color) -> bool) -> Option<(usize, usize)> {
+        self.pixels()
+            .iter()
+            .enumerate()
+            .find_map(|(y, cols)| cols.iter().position(|c| predicate(*c)).map(|x| (x, y)))
+    }
+
+    /// Returns the coordinates of every pixel, in row-major order, for which `predicate` returns true.
+    fn find_all(&self, predicate: impl Fn(Color) -> bool) -> Vec<(usize, usize)> {
+        self.pixels()
+            .iter()
+            .enumerate()
+            .flat_map(|(y, cols)| {
+                cols.iter()
+                    .enumerate()
+                    .filter(|(_, c)| predicate(**c))
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    }
+
+    /// Returns the number of pixels that have each value of each channel.
+    /// For example, `histogram()[0][255]` is the number of pixels whose red channel is 255.
+    fn histogram(&self) -> [[u32; 256]; 3] {
+        let mut bins = [[0; 256]; 3];
+        for c in self.pixels().iter().flatten() {
+            bins[0][c[1] as usize] += 1;
+            bins[1][c[2] as usize] += 1;
+            bins[2][c[3] as usize] += 1;
+        }
+        bins
+    }
+
+    /// Returns the smallest rectangle that contains every pixel that is exactly `color`, or `None` if there are no such pixels.
+    fn bounding_box(&self, color: Color) -> Option<Rect> {
+        bounding_box_where(self.pixels(), |c| *c == color)
+    }
+
+    /// Returns the smallest rectangle that contains every pixel that isn't black, or `None` if every pixel is black.
+    /// This is useful for trimming rendered content before exporting it.
+    fn bounding_box_nonzero(&self) -> Option<Rect> {
+        // Compare each pixel as a u32, so that black rows are skipped quickly.
+        bounding_box_where(self.pixels(), |c| to_u32(*c) != 0)
+    }
+
+    /// Returns a copy of every pixel, row by row, e.g. to check later whether the frame changed with `diff`.
+    fn snapshot(&self) -> Vec<Color> {
+        self.pixels().iter().flatten().copied().collect()
+    }
+
+    /// Returns the smallest rectangle that contains every pixel that is different from `snapshot`, or `None` if nothing changed.
+    ///
+    /// - `snapshot` is the colors of every pixel, row by row, e.g. from `snapshot`.
+    ///
+    /// Returns an `Error` if the length of `snapshot` isn't `X * Y`.
+    fn diff(&self, snapshot: &[Color]) -> Result<Option<Rect>, RgbBufferError> {
+        if snapshot.len() != X * Y {
+            return Err(RgbBufferError::InvalidSize {
+                x: X,
+                y: snapshot.len(),
+            });
+        }
+        if X == 0 {
+            return Ok(None);
+        }
+        let rows = self
+            .pixels()
+            .iter()
+            .zip(snapshot.chunks_exact(X))
+            .map(|(cols, old)| {
+                // Find the first and last changed pixels in this row.
+                let changed = |(c, o): (&Color, &Color)| c != o;
+                let x0 = cols.iter().zip(old).position(changed)?;
+                let x1 = cols.iter().zip(old).rposition(changed).unwrap();
+                Some((x0, x1))
+            });
+        Ok(bounding_box_of_rows(rows))
+    }
+
+    /// Returns the bounding box of the 4-connected region of pixels that start at `(x, y)` and are exactly the same color as it.
+    /// Returns `None` if `(x, y)` is out of bounds.
+    ///
+    /// This allocates one `bool` per pixel to track which pixels have been visited.
+    fn connected_bounds(&self, x: usize, y: usize) -> Option<Rect> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let pixels = self.pixels();
+        let color = pixels[y][x];
+        let mut visited = vec![false; X * Y];
+        let mut stack = vec![(x, y)];
+        visited[y * X + x] = true;
+        let [mut x0, mut y0, mut x1, mut y1] = [x, y, x, y];
+        while let Some((x, y)) = stack.pop() {
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+            // Visit each neighbor that is in bounds, unvisited, and the same color.
+            let neighbors = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in neighbors {
+                if nx < X && ny < Y && !visited[ny * X + nx] && pixels[ny][nx] == color {
+                    visited[ny * X + nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        Some(Rect::new(x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+    }
+
+    /// Replace each pixel's color with `f(color)`.
+    fn map_pixels(&mut self, mut f: impl FnMut(Color) -> Color) {
+        self.pixels_mut()
+            .iter_mut()
+            .flat_map(|cols| cols.iter_mut())
+            .for_each(|c| *c = f(*c));
+    }
+
+    /// Replace every pixel that is exactly `from` with `to`.
+    fn replace_color(&mut self, from: Color, to: Color) {
+        self.pixels_mut()
+            .iter_mut()
+            .flatten()
+            .filter(|c| **c == from)
+            .for_each(|c| *c = to);
+    }
+
+    /// Replace colors using a palette of `(from, to)` pairs.
+    ///
+    /// Each pixel is replaced at most once, so pairs can swap colors: `[(a, b), (b, a)]` swaps `a` and `b`.
+    /// If more than one pair has the same `from` color, the first pair is used.
+    fn replace_colors(&mut self, pairs: &[(Color, Color)]) {
+        self.map_pixels(|c| {
+            pairs
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        });
+    }
+
+    /// Replace every pixel with one of two colors, depending on its `luminance`.
+    ///
+    /// - `luminance_cutoff` is the luminance threshold.
+    /// - `below` is the `[0, r, g, b]` color of pixels whose luminance is less than `luminance_cutoff`.
+    /// - `above` is the `[0, r, g, b]` color of all other pixels.
+    fn threshold(&mut self, luminance_cutoff: u8, below: Color, above: Color) {
+        self.map_pixels(|c| {
+            if luminance(c) < luminance_cutoff {
+                below
+            } else {
+                above
+            }
+        });
+    }
+
+    /// Blur the buffer with a box blur. Pixels beyond the edges of the buffer are treated as copies of the edge pixels.
+    ///
+    /// - `radius` is the distance in pixels to the edge of the box. If it's 0, this does nothing.
+    ///
+    /// The blur is done as a horizontal pass and then a vertical pass with a sliding window, so the cost doesn't depend on `radius`.
+    /// This allocates a scratch buffer of `X * Y` colors. To reuse a scratch buffer, call `blur_with_scratch`.
+    fn blur(&mut self, radius: usize) {
+        if radius == 0 || X == 0 {
+            return;
+        }
+        self.blur_with_scratch(radius, &mut Scratch::new());
+    }
+
+    /// Blur the buffer with a box blur, like `blur`, without allocating.
+    ///
+    /// - `radius` is the distance in pixels to the edge of the box. If it's 0, this does nothing.
+    /// - `scratch` is temporary storage. Its previous contents are overwritten.
+    fn blur_with_scratch(&mut self, radius: usize, scratch: &mut Scratch<X, Y>) {
+        if radius == 0 || X == 0 {
+            return;
+        }
+        let pixels = self.pixels_mut();
+        let scratch = scratch.colors_mut();
+        // Blur each row into the scratch buffer.
+        for (cols, out) in pixels.iter().zip(scratch.chunks_exact_mut(X)) {
+            box_blur_line(X, radius, |x| cols[x], |x, c| out[x] = c);
+        }
+        // Blur each column of the scratch buffer back into the pixels.
+        for x in 0..X {
+            box_blur_line(Y, radius, |y| scratch[y * X + x], |y, c| pixels[y][x] = c);
+        }
+    }
+
+    /// Pixelate the buffer: fill each `block` by `block` square with its `average_color`, e.g. to censor part of an image or for a retro look.
+    ///
+    /// - `block` is the width and height of each square. If it's 1, this does nothing.
+    ///
+    /// The blocks start at the top-left corner. Blocks at the right and bottom edges can be smaller.
+    ///
+    /// Returns an `Error` if `block` is 0.
+    fn pixelate(&mut self, block: usize) -> Result<(), RgbBufferError> {
+        if block == 0 {
+            return Err(RgbBufferError::InvalidSize { x: block, y: block });
+        }
+        if block == 1 {
+            return Ok(());
+        }
+        for y in (0..Y).step_by(block) {
+            for x in (0..X).step_by(block) {
+                let rect = Rect::new(x, y, block, block).clamp_to(X, Y);
+                let color = self.average_color(rect);
+                self.pixels_mut()[rect.y..rect.y + rect.h]
+                    .iter_mut()
+                    .for_each(|cols| cols[rect.x..rect.x + rect.w].fill(color));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reduce each channel to `levels` evenly spaced values with ordered dithering, using a 4x4 Bayer matrix.
+    /// This hides the banding that quantizing a smooth gradient would otherwise cause.
+    ///
+    /// - `levels` is the number of values per channel. If it's less than 2, this does nothing.
+    fn dither_ordered(&mut self, levels: u8) {
+        if levels < 2 {
+            return;
+        }
+        let n = levels as u32 - 1;
+        for (y, cols) in self.pixels_mut().iter_mut().enumerate() {
+            let bayer_row = &BAYER[y % 4];
+            for (x, c) in cols.iter_mut().enumerate() {
+                // Offset each channel by a threshold in `[1/32, 31/32]` before rounding down to a level.
+                let offset = (2 * bayer_row[x % 4] as u32 + 1) * 255;
+                for v in c[1..].iter_mut() {
+                    let level = ((32 * *v as u32 * n + offset) / (32 * 255)).min(n);
+                    *v = ((level * 255 + n / 2) / n) as u8;
+                }
+            }
+        }
+    }
+
+    /// Stretch each channel so that its minimum value becomes 0 and its maximum value becomes 255.
+    ///
+    /// Channels that have only one value aren't changed.
+    fn stretch_contrast(&mut self) {
+        let bins = self.histogram();
+        // Build a lookup table for each channel.
+        let luts: [[u8; 256]; 3] = bins.map(|channel| {
+            let min = channel.iter().position(|n| *n > 0);
+            let max = channel.iter().rposition(|n| *n > 0);
+            match (min, max) {
+                (Some(min), Some(max)) if min < max => {
+                    let range = (max - min) as u32;
+                    std::array::from_fn(|v| {
+                        let v = v.clamp(min, max) - min;
+                        ((v as u32 * 255 + range / 2) / range) as u8
+                    })
+                }
+                _ => std::array::from_fn(|v| v as u8),
+            }
+        });
+        self.apply_lut(&luts);
+    }
+
+    /// Map each channel of every pixel through a lookup table.
+    ///
+    /// - `lut` is the r, g, and b tables. Each channel value `v` becomes `lut[channel][v]`.
+    ///
+    /// Many color adjustments, such as `posterize` and `adjust_brightness`, are a lookup table per channel.
+    fn apply_lut(&mut self, lut: &[[u8; 256]; 3]) {
+        self.map_pixels(|c| {
+            [
+                0,
+                lut[0][c[1] as usize],
+                lut[1][c[2] as usize],
+                lut[2][c[3] as usize],
+            ]
+        });
+    }
+
+    /// Reduce each channel to `levels` evenly spaced values, rounding to the nearest value.
+    ///
+    /// - `levels` is the number of values per channel. If it's less than 2, this does nothing.
+    fn posterize(&mut self, levels: u8) {
+        if levels < 2 {
+            return;
+        }
+        let step = 255.0 / (levels - 1) as f32;
+        let lut = std::array::from_fn(|v| ((v as f32 / step).round() * step).round() as u8);
+        self.apply_lut(&[lut; 3]);
+    }
+
+    /// Add `delta` to each channel of every pixel, clamping to 0 and 255.
+    fn adjust_brightness(&mut self, delta: i16) {
+        let lut = std::array::from_fn(|v| (v as i16 + delta).clamp(0, 255) as u8);
+        self.apply_lut(&[lut; 3]);
+    }
+
+    /// Adjust the saturation of every pixel by interpolating each channel between the pixel's `luminance` and its original value.
+    ///
+    /// - `amount` is the saturation. 0 converts the pixels to gray, 1 doesn't change them, and values above 1 boost the saturation. It's clamped to 0 and 256.
+    ///
+    /// Channels are clamped to 0 and 255.
+    fn adjust_saturation(&mut self, amount: f32) {
+        // Use 8.8 fixed-point math per pixel.
+        // At 256, every channel that differs from the luminance already saturates, so larger amounts wouldn't change anything and could overflow.
+        let k = (amount.clamp(0.0, 256.0) * 256.0).round() as i32;
+        self.map_pixels(|c| {
+            let l = luminance(c) as i32;
+            let adjust = |v: u8| (l + (((v as i32 - l) * k + 128) >> 8)).clamp(0, 255) as u8;
+            [0, adjust(c[1]), adjust(c[2]), adjust(c[3])]
+        });
+    }
+
+    /// Tint the buffer with a sepia tone. Each pixel is converted to its `luminance`, and then the luminance is mapped through a sepia lookup table.
+    fn sepia(&mut self) {
+        // The classic sepia matrix, applied to a gray pixel.
+        let tone = |scale: f32| std::array::from_fn(|v| (v as f32 * scale).min(255.0) as u8);
+        let lut: [[u8; 256]; 3] = [tone(1.351), tone(1.203), tone(0.937)];
+        self.map_pixels(|c| {
+            let l = luminance(c) as usize;
+            [0, lut[0][l], lut[1][l], lut[2][l]]
+        });
+    }
+
+    /// Set every pixel to `f(x, y)`, in row-major order. Unlike `map_pixels`, this doesn't read the existing colors.
+    fn shade(&mut self, mut f: impl FnMut(usize, usize) -> Color) {
+        for (y, cols) in self.pixels_mut().iter_mut().enumerate() {
+            for (x, c) in cols.iter_mut().enumerate() {
+                *c = f(x, y);
+            }
+        }
+    }
+
+    /// Fill the buffer with the classic XOR texture: each pixel is a gray whose value is `x ^ y`, truncated to a u8.
+    /// This is useful as a smoke test, because coordinate and stride bugs are immediately visible.
+    fn fill_xor(&mut self) {
+        self.shade(|x, y| {
+            let v = (x ^ y) as u8;
+            [0, v, v, v]
+        });
+    }
+
+    /// Fill the buffer with an animated sine plasma.
+    ///
+    /// - `t` is the time, e.g. in seconds. Increase it each frame to animate the plasma.
+    fn fill_plasma(&mut self, t: f32) {
+        use std::f32::consts::{PI, TAU};
+        let channel = |v: f32, phase: f32| (127.5 + 127.5 * (PI * v + phase).sin()) as u8;
+        self.shade(|x, y| {
+            let (x, y) = (x as f32, y as f32);
+            // Sum several sine waves. The result is between -4 and 4.
+            let v = (x / 16.0 + t).sin()
+                + (y / 8.0 + t).sin()
+                + ((x + y) / 16.0 + t).sin()
+                + ((x * x + y * y).sqrt() / 8.0 + t).sin();
+            // Offset each channel's phase so that the plasma cycles through colors.
+            [
+                0,
+                channel(v, 0.0),
+                channel(v, TAU / 3.0),
+                channel(v, 2.0 * TAU / 3.0),
+            ]
+        });
+    }
+
+    /// Invert every pixel, e.g. `[0, 255, 20, 5]` becomes `[0, 0, 235, 250]`.
+    ///
+    /// Each pixel is XORed as a u32, which flips the r, g, and b channels at once and leaves the first byte at 0.
+    fn invert(&mut self) {
+        let mask = to_u32([0, 255, 255, 255]);
+        self.pixels_mut()
+            .iter_mut()
+            .flatten()
+            .for_each(|c| *c = from_u32(to_u32(*c) ^ mask));
+    }
+
+    /// Swap the r and b channels of every pixel. This converts BGR colors to RGB colors and vice versa.
+    fn swap_red_blue(&mut self) {
+        self.pixels_mut()
+            .iter_mut()
+            .flatten()
+            .for_each(|c| c.swap(1, 3));
+    }
+
+    /// Copy an image of BGR bytes into the buffer, converting each pixel to `[0, r, g, b]`.
+    ///
+    /// - `x` and `y` are the coordinates of the image's top-left pixel.
+    /// - `width` is the width of the image in pixels.
+    /// - `bgr` is the image's pixels, row by row. Each pixel is three bytes: b, g, and r.
+    ///
+    /// Returns an `Error` if `bgr` is empty or its length isn't a multiple of `width * 3`.
+    /// Pixels that are out of bounds aren't copied.
+    fn blit_bgr(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        bgr: &[u8],
+    ) -> Result<(), RgbBufferError> {
+        let row_len = width.saturating_mul(3);
+        if bgr.is_empty() || !bgr.len().is_multiple_of(row_len) {
+            return Err(RgbBufferError::InvalidSize {
+                x: width,
+                y: bgr.len(),
+            });
+        }
+        let rows = self.pixels_mut().iter_mut().skip(y);
+        for (cols, bgr_row) in rows.zip(bgr.chunks_exact(row_len)) {
+            for (c, b) in cols.iter_mut().skip(x).zip(bgr_row.chunks_exact(3)) {
+                *c = [0, b[2], b[1], b[0]];
+            }
+        }
+        Ok(())
+    }
+
+    /// Rotate the buffer by 180 degrees in place. This is the same as flipping the buffer horizontally and vertically.
+    fn rotate_180(&mut self) {
+        self.pixels_mut().as_flattened_mut().reverse();
+    }
+
+    /// Mirror the top-left quadrant into the other three quadrants, e.g. for a kaleidoscope effect.
+    /// The top-right quadrant is flipped horizontally, the bottom-left quadrant is flipped vertically, and the bottom-right quadrant is flipped both ways.
+    ///
+    /// If `X` or `Y` is odd, the center column or row belongs to the top-left quadrant and isn't changed.
+    fn mirror_quadrant(&mut self) {
+        let pixels = self.pixels_mut();
+        let (top, bottom) = pixels.split_at_mut(Y.div_ceil(2));
+        for cols in top.iter_mut() {
+            let (left, right) = cols.split_at_mut(X.div_ceil(2));
+            // If `X` is odd, `left` is longer, so its last pixel (the center column) isn't copied.
+            right
+                .iter_mut()
+                .rev()
+                .zip(left.iter())
+                .for_each(|(r, l)| *r = *l);
+        }
+        // Likewise, if `Y` is odd, the center row isn't copied.
+        bottom
+            .iter_mut()
+            .rev()
+            .zip(top.iter())
+            .for_each(|(b, t)| *b = *t);
+    }
+
+    /// Returns the pixels of row `y`, or `None` if `y` is out of bounds.
+    fn row(&self, y: usize) -> Option<&[Color]> {
+        self.pixels().get(y).map(|cols| cols.as_slice())
+    }
+
+    /// Returns an iterator over the pixels of column `x`, from top to bottom, or `None` if `x` is out of bounds.
+    fn column_iter(&self, x: usize) -> Option<impl Iterator<Item = &Color>> {
+        (x < X).then(|| self.pixels().iter().map(move |cols| &cols[x]))
+    }
+
+    /// Returns an iterator over every column, from left to right. Each column is an iterator over its pixels, from top to bottom.
+    fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &Color>> {
+        let pixels = self.pixels();
+        (0..X).map(move |x| pixels.iter().map(move |cols| &cols[x]))
+    }
+
+    /// Returns the `4 * X` bytes of row `y` without copying them, or `None` if `y` is out of bounds.
+    /// Each pixel is four bytes: 0, r, g, and b. This is useful for streaming one row at a time to an encoder.
+    fn row_bytes(&self, y: usize) -> Option<&[u8]> {
+        self.pixels().get(y).map(|cols| cols.as_flattened())
+    }
+
+    /// Returns the `4 * X` mutable bytes of row `y`, or `None` if `y` is out of bounds.
+    /// Each pixel is four bytes: 0, r, g, and b. The first byte of each pixel should always be 0.
+    fn row_bytes_mut(&mut self, y: usize) -> Option<&mut [u8]> {
+        self.pixels_mut()
+            .get_mut(y)
+            .map(|cols| cols.as_flattened_mut())
+    }
+
+    /// Returns the pixels as RGBA bytes, row by row. Each pixel is four bytes: r, g, b, and 255.
+    /// This is the layout that most image libraries use, e.g. `image::RgbaImage`.
+    fn to_rgba_vec(&self) -> Vec<u8> {
+        self.pixels()
+            .iter()
+            .flatten()
+            .flat_map(|c| [c[1], c[2], c[3], 255])
+            .collect()
+    }
+
+    /// Copy an image of RGBA bytes into the top-left corner of the buffer, converting each pixel to `[0, r, g, b]`. Alpha is ignored.
+    ///
+    /// - `width` is the width of the image in pixels.
+    /// - `data` is the image's pixels, row by row. Each pixel is four bytes: r, g, b, and a.
+    ///
+    /// Returns an `Error` if `data` is empty or its length isn't a multiple of `width * 4`.
+    /// Pixels that are out of bounds aren't copied.
+    fn load_rgba(&mut self, width: usize, data: &[u8]) -> Result<(), RgbBufferError> {
+        let row_len = width.saturating_mul(4);
+        if data.is_empty() || !data.len().is_multiple_of(row_len) {
+            return Err(RgbBufferError::InvalidSize {
+                x: width,
+                y: data.len(),
+            });
+        }
+        for (cols, rgba_row) in self.pixels_mut().iter_mut().zip(data.chunks_exact(row_len)) {
+            for (c, rgba) in cols.iter_mut().zip(rgba_row.chunks_exact(4)) {
+                *c = [0, rgba[0], rgba[1], rgba[2]];
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy tightly packed RGB bytes into the buffer, converting each pixel to `[0, r, g, b]`. This is the layout of raw image dumps and most decoded JPEGs.
+    ///
+    /// - `data` is the pixels, row by row. Each pixel is three bytes: r, g, and b.
+    ///
+    /// Returns an `Error` if the length of `data` isn't `X * Y * 3`.
+    fn load_rgb(&mut self, data: &[u8]) -> Result<(), RgbBufferError> {
+        if data.len() != X * Y * 3 {
+            return Err(RgbBufferError::InvalidSize {
+                x: X,
+                y: data.len(),
+            });
+        }
+        self.pixels_mut()
+            .iter_mut()
+            .flatten()
+            .zip(data.chunks_exact(3))
+            .for_each(|(c, rgb)| *c = [0, rgb[0], rgb[1], rgb[2]]);
+        Ok(())
+    }
+
+    /// Set each pixel where `predicate(x, y, color)` is true to `color`.
+    /// Unlike `map_pixels`, this only writes the pixels that match.
+    fn fill_if(&mut self, color: Color, predicate: impl Fn(usize, usize, Color) -> bool) {
+        for (y, cols) in self.pixels_mut().iter_mut().enumerate() {
+            for (x, c) in cols.iter_mut().enumerate() {
+                if predicate(x, y, *c) {
+                    *c = color;
+                }
+            }
+        }
+    }
+
+    /// Set one channel of every pixel to `value`.
+    fn set_channel(&mut self, channel: Channel, value: u8) {
+        self.map_channel(channel, |_| value);
+    }
+
+    /// Replace one channel of every pixel with `f(channel value)`.
+    fn map_channel(&mut self, channel: Channel, mut f: impl FnMut(u8) -> u8) {
+        let i = channel.index();
+        self.pixels_mut()
+            .iter_mut()
+            .flat_map(|cols| cols.iter_mut())
+            .for_each(|c| c[i] = f(c[i]));
+    }
+
+    /// Replace each pixel's color with `f(color)`, processing rows in parallel.
+    ///
+    /// The rows are split into chunks of `PAR_ROWS` rows, which are mapped on rayon's thread pool.
+    /// Dispatching the chunks has a fixed cost, so for cheap closures this is only faster than `map_pixels` for large buffers.
+    /// As a rule of thumb, parallelism starts to pay off at around one million pixels (e.g. 1920x1080) and is clearly worth it at 4K.
+    /// For small buffers, or very cheap closures, prefer `map_pixels`.
+    #[cfg(feature = "rayon")]
+    fn par_map_pixels(&mut self, f: impl Fn(Color) -> Color + Sync) {
+        self.pixels_mut().par_chunks_mut(PAR_ROWS).for_each(|rows| {
+            rows.iter_mut()
+                .flat_map(|cols| cols.iter_mut())
+                .for_each(|c| *c = f(*c))
+        });
+    }
+
+    /// Set every pixel to `f(x, y)`, processing rows in parallel. This is the parallel version of `shade`.
+    ///
+    /// The rows are split into chunks of `PAR_ROWS` rows, which are shaded on rayon's thread pool.
+    /// Each pixel only depends on `f(x, y)`, so the result is deterministic and identical to `shade` as long as `f` is a pure function.
+    #[cfg(feature = "rayon")]
+    fn par_shade(&mut self, f: impl Fn(usize, usize) -> Color + Sync) {
+        self.pixels_mut()
+            .par_chunks_mut(PAR_ROWS)
+            .enumerate()
+            .for_each(|(i, rows)| {
+                for (dy, cols) in rows.iter_mut().enumerate() {
+                    let y = i * PAR_ROWS + dy;
+                    for (x, c) in cols.iter_mut().enumerate() {
+                        *c = f(x, y);
+                    }
+                }
+            });
+    }
+
+    /// Draw text using the built-in 8x8 bitmap font.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel of the first character.
+    /// - `text` is the text. Each character is 8 pixels wide. Characters that aren't printable ASCII are drawn as a box.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, color: Color) {
+        self.draw_text_scaled(x, y, text, color, 1);
+    }
+
+    /// Returns the width and height in pixels of the text if it's drawn with `draw_text_scaled`.
+    ///
+    /// - `text` is the text. Each character is `8 * scale` pixels wide.
+    /// - `scale` is the width and height of each pixel of the font.
+    ///
+    /// If `text` is empty, this returns `(0, 0)`.
+    fn measure_text(&self, text: &str, scale: usize) -> (usize, usize) {
+        let size = font::GLYPH_SIZE.saturating_mul(scale);
+        match text.chars().count() {
+            0 => (0, 0),
+            n => (n.saturating_mul(size), size),
+        }
+    }
+
+    /// Draw text using the built-in 8x8 bitmap font, centered around a point.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center of the text.
+    /// - `text` is the text. Each character is `8 * scale` pixels wide. Characters that aren't printable ASCII are drawn as a box.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `scale` is the width and height of each pixel of the font. If it's 0, this does nothing.
+    ///
+    /// If the text would start above or left of the buffer, it's moved to the top or left edge. Pixels that are out of bounds aren't drawn.
+    fn draw_text_centered(&mut self, cx: usize, cy: usize, text: &str, color: Color, scale: usize) {
+        let (w, h) = self.measure_text(text, scale);
+        self.draw_text_scaled(
+            cx.saturating_sub(w / 2),
+            cy.saturating_sub(h / 2),
+            text,
+            color,
+            scale,
+        );
+    }
+
+    /// Draw text using the built-in 8x8 bitmap font, scaled up by an integer factor.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel of the first character.
+    /// - `text` is the text. Each character is `8 * scale` pixels wide. Characters that aren't printable ASCII are drawn as a box.
+    /// - `color` is the `[0, r, g, b]` color.
+    /// - `scale` is the width and height of each pixel of the font. If it's 1, this is the same as `draw_text`. If it's 0, this does nothing.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    fn draw_text_scaled(&mut self, x: usize, y: usize, text: &str, color: Color, scale: usize) {
+        if scale == 0 {
+            return;
+        }
+        let size = font::GLYPH_SIZE.saturating_mul(scale);
+        let pixels = self.pixels_mut();
+        for (i, c) in text.chars().enumerate() {
+            let gx = x.saturating_add(i.saturating_mul(size));
+            if gx >= X {
+                break;
+            }
+            // Fill a block for each pixel of the glyph that is on.
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                let y0 = y.saturating_add(row * scale);
+                if y0 >= Y {
+                    break;
+                }
+                let y1 = y0.saturating_add(scale).min(Y);
+                for col in 0..font::GLYPH_SIZE {
+                    let x0 = gx.saturating_add(col * scale);
+                    if x0 >= X {
+                        break;
+                    }
+                    if bits >> col & 1 == 1 {
+                        let x1 = x0.saturating_add(scale).min(X);
+                        pixels[y0..y1]
+                            .iter_mut()
+                            .for_each(|cols| cols[x0..x1].fill(color));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draw the outline of an axis-aligned ellipse using the midpoint ellipse algorithm.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `rx` and `ry` are the horizontal and vertical radii. If either is 0, this draws a line.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn. Radii larger than `i32::MAX` are clamped.
+    fn draw_ellipse(&mut self, cx: usize, cy: usize, rx: usize, ry: usize, color: Color) {
+        let pixels = self.pixels_mut();
+        let (cx, cy) = (cx as isize, cy as isize);
+        let (rx, ry) = (ellipse_radius(rx), ellipse_radius(ry));
+        // Degenerate ellipses are lines.
+        if rx == 0 || ry == 0 {
+            for y in circle_rows(cy, ry, Y) {
+                for x in circle_rows(cx, rx, X) {
+                    plot::<X, Y>(pixels, cx + x, cy + y, color);
+                }
+            }
+            return;
+        }
+        let quadrant = EllipseQuadrant::new(rx, ry);
+        // Region 1 has one point per column. `circle_rows` also works for columns.
+        for dx in circle_rows(cx, quadrant.x2 - 1, X) {
+            let y = quadrant.column(dx.abs());
+            plot::<X, Y>(pixels, cx + dx, cy.saturating_add(y), color);
+            plot::<X, Y>(pixels, cx + dx, cy.saturating_sub(y), color);
+        }
+        // Region 2 has one point per row.
+        for dy in circle_rows(cy, quadrant.y2, Y) {
+            let x = quadrant.row(dy.abs());
+            plot::<X, Y>(pixels, cx.saturating_add(x), cy + dy, color);
+            plot::<X, Y>(pixels, cx.saturating_sub(x), cy + dy, color);
+        }
+    }
+
+    /// Fill an axis-aligned ellipse. The edge matches the outline that `draw_ellipse` draws.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `rx` and `ry` are the horizontal and vertical radii. If either is 0, this draws a line, and if both are 0, this sets a single pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn. Radii larger than `i32::MAX` are clamped.
+    fn fill_ellipse(&mut self, cx: usize, cy: usize, rx: usize, ry: usize, color: Color) {
+        let pixels = self.pixels_mut();
+        let (cx, cy) = (cx as isize, cy as isize);
+        let (rx, ry) = (ellipse_radius(rx), ellipse_radius(ry));
+        // Degenerate ellipses are lines.
+        if rx == 0 || ry == 0 {
+            for y in circle_rows(cy, ry, Y) {
+                fill_span::<X, Y>(
+                    pixels,
+                    cy + y,
+                    cx.saturating_sub(rx),
+                    cx.saturating_add(rx),
+                    color,
+                );
+            }
+            return;
+        }
+        let quadrant = EllipseQuadrant::new(rx, ry);
+        for dy in circle_rows(cy, ry, Y) {
+            let w = quadrant.half_width(dy.abs());
+            fill_span::<X, Y>(
+                pixels,
+                cy + dy,
+                cx.saturating_sub(w),
+                cx.saturating_add(w),
+                color,
+            );
+        }
+    }
+
+    /// Encode the buffer with `encoder`, e.g. a `PpmEncoder`.
+    /// The leading zero of each color is dropped, so the encoder receives `[r, g, b]` colors, row by row.
+    fn export(&self, encoder: &mut impl Encoder) -> io::Result<()> {
+        encoder.encode(
+            X,
+            Y,
+            self.pixels().iter().flatten().map(|c| [c[1], c[2], c[3]]),
+        )
+    }
+
+    /// Save the buffer as an 8-bit RGB PNG file.
+    /// Rows are encoded one at a time, so this doesn't copy the whole buffer.
+    #[cfg(feature = "png")]
+    fn save_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(File::create(path)?), X as u32, Y as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
+        // Drop the leading zero of each color.
+        let mut row = vec![0; X * 3];
+        for cols in self.pixels().iter() {
+            row.chunks_exact_mut(3)
+                .zip(cols)
+                .for_each(|(rgb, c)| rgb.copy_from_slice(&c[1..]));
+            stream.write_all(&row)?;
+        }
+        stream.finish()
+    }
+
+    /// Returns a drawing context that combines every color it draws with the existing pixels using `mode`.
+    /// The context supports `set_pixel`, `fill_rect`, `draw_line`, and `fill_circle`. See `DrawContext`.
+    fn draw_context(&mut self, mode: BlendMode) -> DrawContext<'_, X> {
+        DrawContext::new(self.pixels_mut(), mode)
+    }
+
+    /// Returns a mutable view of a region of the buffer, with its own local coordinates.
+    /// `rect` is clipped to the buffer.
+    fn region_mut(&mut self, rect: Rect) -> RgbSubView<'_, X> {
+        RgbSubView::new(self.pixels_mut(), rect.clamp_to(X, Y))
+    }
+}
+
+/// Returns the channel-wise saturating sum of two colors. The first byte is always 0.
+fn add(a: Color, b: Color) -> Color {
+    [
+        0,
+        a[1].saturating_add(b[1]),
+        a[2].saturating_add(b[2]),
+        a[3].saturating_add(b[3]),
+    ]
+}
+
+/// Converts an ellipse's radius to an `isize` that is small enough for `EllipseQuadrant` to not overflow.
+fn ellipse_radius(radius: usize) -> isize {
+    radius.min(i32::MAX as usize) as isize
+}
+
+/// Returns the smallest integer whose square is at least `n`.
+fn ceil_isqrt(n: u128) -> u128 {
+    let s = n.isqrt();
+    if s * s == n {
+        s
+    } else {
+        s + 1
+    }
+}
+
+/// The bottom-right quarter of an ellipse, with the same points as the midpoint ellipse algorithm.
+/// Unlike the algorithm, each point is computed directly, so ellipses can be clipped to the buffer even if their radii are huge.
+///
+/// Region 1 is the part where the slope is shallower than -1. It has one point per column, from `x = 0` to `x = x2 - 1`.
+/// Region 2 is the rest. It has one point per row, from `y = y2` to `y = 0`.
+struct EllipseQuadrant {
+    /// The square of the horizontal radius.
+    rx2: u128,
+    /// The square of the vertical radius.
+    ry2: u128,
+    /// The vertical radius.
+    ry: isize,
+    /// The first point of region 2.
+    x2: isize,
+    y2: isize,
+}
+
+impl EllipseQuadrant {
+    /// `rx` and `ry` must be positive and at most `i32::MAX`, so that the math can't overflow.
+    fn new(rx: isize, ry: isize) -> Self {
+        let mut quadrant = Self {
+            rx2: (rx * rx) as u128,
+            ry2: (ry * ry) as u128,
+            ry,
+            x2: 0,
+            y2: 0,
+        };
+        // Region 1 ends at the first column where the slope is at least as steep as -1.
+        let steep = |x: isize| {
+            quadrant.ry2 as i128 * x as i128 >= quadrant.rx2 as i128 * quadrant.step(x) as i128
+        };
+        let x2 = partition_point(rx as i128, |x| !steep(x as isize)) as isize;
+        quadrant.y2 = quadrant.step(x2);
+        quadrant.x2 = x2;
+        quadrant
+    }
+
+    /// Returns the y coordinate of column `x` of region 1.
+    /// This is the row whose lower midpoint is inside the ellipse and whose upper midpoint isn't.
+    fn column(&self, x: isize) -> isize {
+        // Solve `rx2 * (2y + 1)^2 >= 4 * ry2 * (rx2 - x^2)` for the smallest `y`.
+        let x = x as u128;
+        let t = 4 * self.ry2 * (self.rx2 - x * x);
+        (ceil_isqrt(t.div_ceil(self.rx2)) / 2) as isize
+    }
+
+    /// Returns the y coordinate that the midpoint algorithm reaches in column `x`.
+    /// This is `column(x)`, except that the algorithm can only step down by one row per column.
+    fn step(&self, x: isize) -> isize {
+        if x == 0 {
+            return self.ry;
+        }
+        let y = self.column(x - 1);
+        // Step down if the midpoint below `(x, y)` isn't inside the ellipse.
+        let (xu, k) = (x as u128, (2 * y - 1).unsigned_abs() as u128);
+        if 4 * self.ry2 * xu * xu + self.rx2 * k * k >= 4 * self.rx2 * self.ry2 {
+            y - 1
+        } else {
+            y
+        }
+    }
+
+    /// Returns the largest x coordinate of any point in row `y`. `y` must be in `0..=ry`.
+    fn half_width(&self, y: isize) -> isize {
+        if y <= self.y2 {
+            return self.row(y);
+        }
+        // Find the last column of region 1 that reaches row `y`.
+        // Solve `4 * ry2 * x^2 < rx2 * (4 * ry2 - (2y - 1)^2)` for the largest `x`.
+        let k = (2 * y - 1) as u128;
+        let n = self.rx2 * (4 * self.ry2 - k * k);
+        (((n - 1) / (4 * self.ry2)).isqrt() as isize).min(self.x2 - 1)
+    }
+
+    /// Returns the x coordinate of row `y` of region 2. `y` must be at most `y2`.
+    fn row(&self, y: isize) -> isize {
+        // Solve `ry2 * (2x + 1)^2 > 4 * rx2 * (ry2 - y^2) + ry2` for the smallest `x`.
+        let yu = y as u128;
+        let v = 4 * self.rx2 * (self.ry2 - yu * yu) + self.ry2;
+        let x = (ceil_isqrt(v / self.ry2 + 1) / 2) as isize;
+        // Like in region 1, the algorithm can only step right by one column per row.
+        (self.x2 + self.y2 - y).min(x.max(self.x2))
+    }
+}
+
+/// Returns the smallest rectangle that contains every pixel for which `f` returns true, or `None` if there are no such pixels.
+fn bounding_box_where<const X: usize>(
+    pixels: &[[Color; X]],
+    f: impl Fn(&Color) -> bool,
+) -> Option<Rect> {
+    bounding_box_of_rows(pixels.iter().map(|cols| {
+        // Find the first and last matching pixels in this row.
+        let x0 = cols.iter().position(&f)?;
+        let x1 = cols.iter().rposition(&f).unwrap();
+        Some((x0, x1))
+    }))
+}
+
+/// Returns the smallest rectangle that contains every row's span, or `None` if there are no spans.
+/// Each item of `rows` is the first and last x coordinates of a span in that row, or `None` if the row is empty.
+fn bounding_box_of_rows(rows: impl Iterator<Item = Option<(usize, usize)>>) -> Option<Rect> {
+    let mut bounds: Option<[usize; 4]> = None;
+    for (y, (x0, x1)) in rows
+        .enumerate()
+        .filter_map(|(y, span)| span.map(|span| (y, span)))
+    {
+        bounds = Some(match bounds {
+            Some([bx0, by0, bx1, _]) => [bx0.min(x0), by0, bx1.max(x1), y],
+            None => [x0, y, x1, y],
+        });
+    }
+    bounds.map(|[x0, y0, x1, y1]| Rect::new(x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+}
+
+/// Returns the half-width of row `dy` of a circle filled by `Canvas::fill_circle`. `dy` must be in `-r..=r`.
+pub(crate) fn circle_half_width(r: isize, dy: isize) -> isize {
+    let (r, dy) = (r as i128, dy as i128);
+    // Adding `r` to `r * r` rounds the edges like the midpoint circle algorithm.
+    // This can't overflow, because `r` is at most `isize::MAX`.
+    ((r * r + r - dy * dy) as u128).isqrt() as isize
+}
+
+/// Converts a circle's radius to an `isize`, so that huge radii don't wrap around to negative values.
+pub(crate) fn circle_radius(radius: usize) -> isize {
+    radius.min(isize::MAX as usize) as isize
+}
+
+/// Returns the offsets from `cy` of the rows of a circle with radius `r` that are in a buffer that is `height` rows tall.
+/// Rows that are out of bounds are skipped, so this is fast even if the radius is huge.
+pub(crate) fn circle_rows(cy: isize, r: isize, height: usize) -> RangeInclusive<isize> {
+    let bottom = (height as isize - 1).saturating_sub(cy);
+    (-r).max(cy.saturating_neg())..=r.min(bottom)
+}
+
+/// Draw a line from `a` to `b`, rounding each end to the nearest pixel. Pixels that are out of bounds aren't drawn.
+fn plot_segment<const X: usize, const Y: usize>(
+    pixels: &mut [[Color; X]],
+    a: (f32, f32),
+    b: (f32, f32),
+    color: Color,
+) {
+    let (x0, y0) = (a.0.round() as isize, a.1.round() as isize);
+    let (x1, y1) = (b.0.round() as isize, b.1.round() as isize);
+    // Skip segments that are entirely beyond one edge of the buffer.
+    if (x0 < 0 && x1 < 0)
+        || (y0 < 0 && y1 < 0)
+        || (x0 >= X as isize && x1 >= X as isize)
+        || (y0 >= Y as isize && y1 >= Y as isize)
+    {
+        return;
+    }
+    for (x, y) in line_points(x0, y0, x1, y1) {
+        plot::<X, Y>(pixels, x, y, color);
+    }
+}
+
+/// Set the pixel at `(x, y)` if it's in bounds.
+fn plot<const X: usize, const Y: usize>(
+    pixels: &mut [[Color; X]],
+    x: isize,
+    y: isize,
+    color: Color,
+) {
+    if x >= 0 && y >= 0 && (x as usize) < X && (y as usize) < Y {
+        pixels[y as usize][x as usize] = color;
+    }
+}
+
+/// Box blur `len` colors that are read with `src` and written with `dst`.
+/// Colors beyond either end of the line are treated as copies of the end colors.
+fn box_blur_line(
+    len: usize,
+    radius: usize,
+    src: impl Fn(usize) -> Color,
+    mut dst: impl FnMut(usize, Color),
+) {
+    if len == 0 {
+        return;
+    }
+    let last = len - 1;
+    let size = 2 * radius as u64 + 1;
+    // The sum of each channel in the window around the current color.
+    // The window starts with `radius + 1` copies of the first color.
+    let mut sums = [0; 3];
+    let add = |sums: &mut [u64; 3], c: Color, n: u64| {
+        sums.iter_mut()
+            .zip(&c[1..])
+            .for_each(|(sum, v)| *sum += *v as u64 * n);
+    };
+    add(&mut sums, src(0), radius as u64 + 1);
+    (1..=radius.min(last)).for_each(|i| add(&mut sums, src(i), 1));
+    if radius > last {
+        add(&mut sums, src(last), (radius - last) as u64);
+    }
+    for i in 0..len {
+        let [r, g, b] = sums.map(|sum| ((sum + size / 2) / size) as u8);
+        dst(i, [0, r, g, b]);
+        // Slide the window to the right.
+        let next = src(i.saturating_add(radius).saturating_add(1).min(last));
+        let prev = src(i.saturating_sub(radius));
+        sums.iter_mut()
+            .zip(next[1..].iter().zip(&prev[1..]))
+            .for_each(|(sum, (n, p))| *sum = *sum + *n as u64 - *p as u64);
+    }
+}
+
+/// Clip a line to the area from `(0, 0)` to `(max_x, max_y)` (inclusive) using the Cohen-Sutherland algorithm.
+/// Returns the clipped endpoints, or `None` if the line is entirely outside of the area.
+fn clip_line(
+    x0: isize,
+    y0: isize,
+    x1: isize,
+    y1: isize,
+    max_x: isize,
+    max_y: isize,
+) -> Option<(isize, isize, isize, isize)> {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const TOP: u8 = 4;
+    const BOTTOM: u8 = 8;
+    let (max_x, max_y) = (max_x as f64, max_y as f64);
+    let outcode = |x: f64, y: f64| {
+        let mut code = 0;
+        if x < 0.0 {
+            code |= LEFT;
+        } else if x > max_x {
+            code |= RIGHT;
+        }
+        if y < 0.0 {
+            code |= TOP;
+        } else if y > max_y {
+            code |= BOTTOM;
+        }
+        code
+    };
+    let (mut x0, mut y0, mut x1, mut y1) = (x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+    let mut code0 = outcode(x0, y0);
+    let mut code1 = outcode(x1, y1);
+    loop {
+        if code0 | code1 == 0 {
+            // Both endpoints are inside.
+            return Some((
+                x0.round() as isize,
+                y0.round() as isize,
+                x1.round() as isize,
+                y1.round() as isize,
+            ));
+        }
+        if code0 & code1 != 0 {
+            // Both endpoints are beyond the same edge.
+            return None;
+        }
+        // Move an outside endpoint to where the line crosses the edge that it's beyond.
+        let code = if code0 != 0 { code0 } else { code1 };
+        let (x, y) = if code & TOP != 0 {
+            (x0 + (x1 - x0) * (0.0 - y0) / (y1 - y0), 0.0)
+        } else if code & BOTTOM != 0 {
+            (x0 + (x1 - x0) * (max_y - y0) / (y1 - y0), max_y)
+        } else if code & RIGHT != 0 {
+            (max_x, y0 + (y1 - y0) * (max_x - x0) / (x1 - x0))
+        } else {
+            (0.0, y0 + (y1 - y0) * (0.0 - x0) / (x1 - x0))
+        };
+        if code == code0 {
+            (x0, y0) = (x, y);
+            code0 = outcode(x0, y0);
+        } else {
+            (x1, y1) = (x, y);
+            code1 = outcode(x1, y1);
+        }
+    }
+}
+
+/// Returns twice the signed area of the triangle `a`, `b`, `p`.
+/// It's positive if `p` is on one side of the line from `a` to `b`, negative if it's on the other side, and 0 if it's on the line.
+fn edge(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+/// The maximum number of times that a Bézier curve is subdivided.
+const MAX_BEZIER_DEPTH: u32 = 16;
+
+/// Split a quadratic Bézier curve in half until each half is flat, and then call `f` with the start and end of each flat piece.
+fn bezier_quadratic_segments(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    depth: u32,
+    f: &mut impl FnMut((f32, f32), (f32, f32)),
+) {
+    // The curve's maximum distance from the line `p0`-`p2` is a quarter of this vector's length.
+    let dx = p0.0 - 2.0 * p1.0 + p2.0;
+    let dy = p0.1 - 2.0 * p1.1 + p2.1;
+    // Stop when the distance is less than 0.25, i.e. when the vector's squared length is less than 1.
+    if depth >= MAX_BEZIER_DEPTH || dx * dx + dy * dy <= 1.0 || !(dx.is_finite() && dy.is_finite())
+    {
+        f(p0, p2);
+    } else {
+        // Split the curve at `t = 0.5` with de Casteljau's algorithm.
+        let a = midpoint(p0, p1);
+        let b = midpoint(p1, p2);
+        let m = midpoint(a, b);
+        bezier_quadratic_segments(p0, a, m, depth + 1, f);
+        bezier_quadratic_segments(m, b, p2, depth + 1, f);
+    }
+}
+
+fn midpoint(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// Fill the pixels from `x0` to `x1` (inclusive) in row `y`, clipped to the buffer.
+fn fill_span<const X: usize, const Y: usize>(
+    pixels: &mut [[Color; X]],
+    y: isize,
+    x0: isize,
+    x1: isize,
+    color: Color,
+) {
+    if y < 0 || y >= Y as isize || x1 < 0 || x0 >= X as isize || x0 > x1 {
+        return;
+    }
+    let x0 = x0.max(0) as usize;
+    let x1 = (x1 as usize).min(X - 1);
+    pixels[y as usize][x0..=x1].fill(color);
+}
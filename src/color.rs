@@ -0,0 +1,254 @@
+/// An `[0, r, g, b]` color. The first element is always 0.
+pub type Color = [u8; 4];
+
+/// Black.
+pub const BLACK: Color = rgb(0, 0, 0);
+/// White.
+pub const WHITE: Color = rgb(255, 255, 255);
+/// 50% gray.
+pub const GRAY: Color = rgb(128, 128, 128);
+/// Red.
+pub const RED: Color = rgb(255, 0, 0);
+/// Green.
+pub const GREEN: Color = rgb(0, 255, 0);
+/// Blue.
+pub const BLUE: Color = rgb(0, 0, 255);
+/// Yellow.
+pub const YELLOW: Color = rgb(255, 255, 0);
+/// Cyan.
+pub const CYAN: Color = rgb(0, 255, 255);
+/// Magenta.
+pub const MAGENTA: Color = rgb(255, 0, 255);
+
+/// Returns the `[0, r, g, b]` color.
+pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    [0, r, g, b]
+}
+
+/// Convert a softbuffer `u32` value into a color.
+pub const fn from_u32(value: u32) -> Color {
+    value.to_le_bytes()
+}
+
+/// Convert a color into a softbuffer `u32` value.
+pub const fn to_u32(color: Color) -> u32 {
+    u32::from_le_bytes(color)
+}
+
+/// A typed RGB color. Unlike `Color`, this can't be mistaken for an `[r, g, b, a]` array.
+///
+/// Convert it to a `Color` with `to_color` or `Color::from`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Returns the red channel.
+    pub const fn r(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the green channel.
+    pub const fn g(self) -> u8 {
+        self.1
+    }
+
+    /// Returns the blue channel.
+    pub const fn b(self) -> u8 {
+        self.2
+    }
+
+    /// Returns the `[0, r, g, b]` color.
+    pub const fn to_color(self) -> Color {
+        rgb(self.0, self.1, self.2)
+    }
+
+    /// Convert an `[0, r, g, b]` color. The first element is ignored.
+    pub const fn from_color(color: Color) -> Self {
+        Self(color[1], color[2], color[3])
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(value: Rgb) -> Self {
+        value.to_color()
+    }
+}
+
+impl From<Color> for Rgb {
+    fn from(value: Color) -> Self {
+        Self::from_color(value)
+    }
+}
+
+/// The byte order of a 4-byte color. Use this to convert colors from and to other sources.
+///
+/// `Color` and every buffer in this crate always use `Xrgb`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ColorFormat {
+    /// `[0, r, g, b]`. This is the layout of `Color`.
+    #[default]
+    Xrgb,
+    /// `[r, g, b, 0]`.
+    Rgbx,
+    /// `[0, b, g, r]`.
+    Xbgr,
+    /// `[b, g, r, 0]`.
+    Bgrx,
+}
+
+impl ColorFormat {
+    /// Returns the indices of the r, g, and b bytes.
+    const fn indices(self) -> [usize; 3] {
+        match self {
+            Self::Xrgb => [1, 2, 3],
+            Self::Rgbx => [0, 1, 2],
+            Self::Xbgr => [3, 2, 1],
+            Self::Bgrx => [2, 1, 0],
+        }
+    }
+
+    /// Returns the four bytes of the color `(r, g, b)` in this format. The unused byte is 0.
+    pub const fn rgb(self, r: u8, g: u8, b: u8) -> [u8; 4] {
+        let [ri, gi, bi] = self.indices();
+        let mut bytes = [0; 4];
+        bytes[ri] = r;
+        bytes[gi] = g;
+        bytes[bi] = b;
+        bytes
+    }
+
+    /// Convert four bytes in this format into an `[0, r, g, b]` color.
+    pub const fn to_color(self, bytes: [u8; 4]) -> Color {
+        let [ri, gi, bi] = self.indices();
+        rgb(bytes[ri], bytes[gi], bytes[bi])
+    }
+
+    /// Convert an `[0, r, g, b]` color into four bytes in this format.
+    pub const fn from_color(self, color: Color) -> [u8; 4] {
+        self.rgb(color[1], color[2], color[3])
+    }
+}
+
+/// A color channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Red.
+    R,
+    /// Green.
+    G,
+    /// Blue.
+    B,
+}
+
+impl Channel {
+    /// Returns the index of this channel's byte in a `Color`.
+    pub(crate) const fn index(self) -> usize {
+        match self {
+            Self::R => 1,
+            Self::G => 2,
+            Self::B => 3,
+        }
+    }
+}
+
+/// Returns the perceived brightness of `color`, from 0 to 255.
+/// This uses the integer approximation of the Rec. 601 luma weights: `(77r + 150g + 29b) / 256`.
+pub const fn luminance(color: Color) -> u8 {
+    ((77 * color[1] as u16 + 150 * color[2] as u16 + 29 * color[3] as u16) >> 8) as u8
+}
+
+/// Alpha-blend `src` over `dst`, where `alpha` is the opacity of `src`.
+/// If `alpha` is 255, this returns `src`. If `alpha` is 0, this returns `dst`.
+pub const fn blend(dst: Color, src: Color, alpha: u8) -> Color {
+    [
+        0,
+        blend_channel(dst[1], src[1], alpha),
+        blend_channel(dst[2], src[2], alpha),
+        blend_channel(dst[3], src[3], alpha),
+    ]
+}
+
+const fn blend_channel(dst: u8, src: u8, alpha: u8) -> u8 {
+    ((src as u16 * alpha as u16 + dst as u16 * (255 - alpha as u16) + 127) / 255) as u8
+}
+
+/// How a `DrawContext` combines the color that is drawn (`src`) with the existing pixel (`dst`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Replace the pixel with `src`.
+    #[default]
+    Replace,
+    /// Alpha-blend `src` over the pixel with `blend`, where the value is the opacity of `src`.
+    Alpha(u8),
+    /// Add each channel of `src` to the pixel, saturating at 255. Overlapping draws get brighter.
+    Add,
+    /// Multiply each channel of the pixel by the channel of `src` divided by 255. Overlapping draws get darker.
+    Multiply,
+}
+
+impl BlendMode {
+    /// Returns the result of drawing `src` over `dst` with this mode.
+    pub const fn apply(self, dst: Color, src: Color) -> Color {
+        match self {
+            Self::Replace => [0, src[1], src[2], src[3]],
+            Self::Alpha(alpha) => blend(dst, src, alpha),
+            Self::Add => [
+                0,
+                dst[1].saturating_add(src[1]),
+                dst[2].saturating_add(src[2]),
+                dst[3].saturating_add(src[3]),
+            ],
+            Self::Multiply => [
+                0,
+                multiply_channel(dst[1], src[1]),
+                multiply_channel(dst[2], src[2]),
+                multiply_channel(dst[3], src[3]),
+            ],
+        }
+    }
+}
+
+const fn multiply_channel(dst: u8, src: u8) -> u8 {
+    ((dst as u16 * src as u16 + 127) / 255) as u8
+}
+
+/// Multiply the r, g, and b channels of `color` by `alpha / 255`.
+pub const fn premultiply(color: Color, alpha: u8) -> Color {
+    [
+        0,
+        premultiply_channel(color[1], alpha),
+        premultiply_channel(color[2], alpha),
+        premultiply_channel(color[3], alpha),
+    ]
+}
+
+/// Divide the r, g, and b channels of a premultiplied `color` by `alpha / 255`. This is the inverse of `premultiply`.
+///
+/// Premultiplying loses precision, so this can't perfectly recover the original color.
+/// The error grows as `alpha` shrinks: it's up to about `128 / alpha` per channel.
+/// If `alpha` is 0, this returns black.
+pub const fn unpremultiply(color: Color, alpha: u8) -> Color {
+    if alpha == 0 {
+        [0; 4]
+    } else {
+        [
+            0,
+            unpremultiply_channel(color[1], alpha),
+            unpremultiply_channel(color[2], alpha),
+            unpremultiply_channel(color[3], alpha),
+        ]
+    }
+}
+
+const fn premultiply_channel(c: u8, alpha: u8) -> u8 {
+    ((c as u16 * alpha as u16 + 127) / 255) as u8
+}
+
+const fn unpremultiply_channel(c: u8, alpha: u8) -> u8 {
+    let v = (c as u16 * 255 + alpha as u16 / 2) / alpha as u16;
+    if v > 255 {
+        255
+    } else {
+        v as u8
+    }
+}
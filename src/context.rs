@@ -0,0 +1,110 @@
+use crate::canvas::{circle_half_width, circle_radius, circle_rows};
+use crate::{line_points, BlendMode, Color, Rect};
+
+/// A drawing context that writes every pixel with a `BlendMode`. See `Canvas::draw_context`.
+///
+/// Unlike `Canvas` methods such as `fill_circle`, which replace pixels, every method here combines the color with the existing pixel.
+/// This avoids separate alpha-blended and additive variants of each primitive.
+///
+/// Only these operations use the blend mode:
+///
+/// - `set_pixel`
+/// - `fill_rect`
+/// - `draw_line`
+/// - `fill_circle`
+///
+/// A `DrawContext` isn't a `Canvas`. Other drawing methods, such as `draw_text` or `fill_polygon`, are only available on the buffer and always replace pixels.
+pub struct DrawContext<'a, const X: usize> {
+    /// The rows of the buffer.
+    rows: &'a mut [[Color; X]],
+    /// How colors are combined with the existing pixels.
+    mode: BlendMode,
+}
+
+impl<'a, const X: usize> DrawContext<'a, X> {
+    pub(crate) fn new(rows: &'a mut [[Color; X]], mode: BlendMode) -> Self {
+        Self { rows, mode }
+    }
+
+    /// Returns the blend mode.
+    pub const fn mode(&self) -> BlendMode {
+        self.mode
+    }
+
+    /// Set the blend mode. This affects every subsequent draw.
+    pub fn set_mode(&mut self, mode: BlendMode) {
+        self.mode = mode;
+    }
+
+    /// Draw a pixel. If the position is out of bounds, this does nothing.
+    ///
+    /// - `x` and `y` are the coordinates of the pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if let Some(pixel) = self.rows.get_mut(y).and_then(|cols| cols.get_mut(x)) {
+            *pixel = self.mode.apply(*pixel, color);
+        }
+    }
+
+    /// Fill a rectangle. The rectangle is clipped to the buffer.
+    ///
+    /// - `rect` is the rectangle.
+    /// - `color` is the `[0, r, g, b]` color.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rect = rect.clamp_to(X, self.rows.len());
+        for y in rect.y..rect.y + rect.h {
+            self.span(
+                y as isize,
+                rect.x as isize,
+                (rect.x + rect.w) as isize - 1,
+                color,
+            );
+        }
+    }
+
+    /// Draw a 1-pixel line using Bresenham's line algorithm, like `Canvas::draw_line`.
+    ///
+    /// - `x0` and `y0` are the coordinates of the start of the line.
+    /// - `x1` and `y1` are the coordinates of the end of the line.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    pub fn draw_line(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, color: Color) {
+        let height = self.rows.len();
+        for (x, y) in
+            line_points(x0 as isize, y0 as isize, x1 as isize, y1 as isize).clip(X, height)
+        {
+            self.span(y, x, x, color);
+        }
+    }
+
+    /// Fill a circle, like `Canvas::fill_circle`.
+    ///
+    /// - `cx` and `cy` are the coordinates of the center.
+    /// - `radius` is the radius. If it's 0, this draws a single pixel.
+    /// - `color` is the `[0, r, g, b]` color.
+    ///
+    /// Pixels that are out of bounds aren't drawn.
+    pub fn fill_circle(&mut self, cx: usize, cy: usize, radius: usize, color: Color) {
+        let (cx, cy, r) = (cx as isize, cy as isize, circle_radius(radius));
+        for dy in circle_rows(cy, r, self.rows.len()) {
+            let dx = circle_half_width(r, dy);
+            self.span(cy + dy, cx.saturating_sub(dx), cx.saturating_add(dx), color);
+        }
+    }
+
+    /// Draw the pixels from `x0` to `x1` (inclusive) in row `y`, clipped to the buffer.
+    fn span(&mut self, y: isize, x0: isize, x1: isize, color: Color) {
+        if y < 0 || x1 < 0 || x0 >= X as isize || x0 > x1 {
+            return;
+        }
+        if let Some(cols) = self.rows.get_mut(y as usize) {
+            let x0 = x0.max(0) as usize;
+            let x1 = (x1 as usize).min(X - 1);
+            let mode = self.mode;
+            cols[x0..=x1]
+                .iter_mut()
+                .for_each(|c| *c = mode.apply(*c, color));
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::io::{self, Write};
+
+/// An image format encoder for `Canvas::export`.
+/// The buffer supplies its pixels as `[r, g, b]` colors, and the encoder decides how to write them.
+pub trait Encoder {
+    /// Encode an image.
+    ///
+    /// - `width` and `height` are the dimensions of the image.
+    /// - `rgb` is the `[r, g, b]` color of every pixel, row by row.
+    fn encode(
+        &mut self,
+        width: usize,
+        height: usize,
+        rgb: impl Iterator<Item = [u8; 3]>,
+    ) -> io::Result<()>;
+}
+
+/// Encodes images as binary PPM (P6) files.
+/// PPM is uncompressed and can be opened by most image viewers and editors.
+///
+/// Each color is written separately, so `writer` should usually be buffered, e.g. a `BufWriter<File>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PpmEncoder<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PpmEncoder<W> {
+    /// Returns a new encoder that writes to `writer`.
+    pub const fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Encoder for PpmEncoder<W> {
+    fn encode(
+        &mut self,
+        width: usize,
+        height: usize,
+        rgb: impl Iterator<Item = [u8; 3]>,
+    ) -> io::Result<()> {
+        write!(self.writer, "P6\n{width} {height}\n255\n")?;
+        for c in rgb {
+            self.writer.write_all(&c)?;
+        }
+        self.writer.flush()
+    }
+}
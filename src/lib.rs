@@ -9,7 +9,7 @@
 //! ...you can now do this:
 //! 
 //! ```ignore
-//! buffer.pixels[y][x] = [0, 200, 70, 10];
+//! buffer.pixels_mut()[y][x] = [0, 200, 70, 10];
 //! ```
 //!
 //! ## Problem
@@ -46,18 +46,45 @@
 //!
 //! `softbuffer-rgb` relies on generic constants to define the size of `pixels`, meaning that the buffer size must be known at compile-time.
 //!
+//! **An `RgbBuffer` can't be resized at runtime.**
+//! If the window is resized, the surface must still be `X` by `Y` pixels, or else `from_softbuffer` will return an error.
+//! `RgbBuffer::from_surface` resizes the surface to `X` by `Y` and then gets its buffer, so it's safe to call every frame regardless of the window's size.
+//! `RgbSurface` wraps a `Surface` and does this for you: `frame` creates an `RgbBuffer`, runs your draw function, and presents the buffer.
+//! If you need to fill a window of any size, choose the largest size that you'll support, or use `softbuffer` directly.
+//!
+//! ## Drawing
+//!
+//! Drawing methods such as `fill` and `fill_rectangle` are in the `Canvas` trait.
+//! `Canvas` is implemented by `RgbBuffer` and by `OwnedRgbBuffer`, which owns its pixels and doesn't need a window.
+//!
+//! `rgb(r, g, b)` returns the `[0, r, g, b]` color, and there are constants for common colors such as `BLACK` and `WHITE`.
+//! `Rgb` is a typed color that can't be mistaken for an `[r, g, b, a]` array. Convert it to a `Color` with `to_color`, or use methods such as `fill_rgb`.
+//! To convert colors from or to other byte orders, such as `[r, g, b, 0]`, use `ColorFormat`.
+//!
+//! To draw with alpha blending, additive blending, or multiplication instead of replacing pixels, use a `DrawContext`, e.g. `rgb_buffer.draw_context(BlendMode::Add)`.
+//!
+//! To accumulate additive light without clipping, e.g. for glow effects, add samples to an `AccumBuffer` and then tone-map it into a buffer with `resolve_into`.
+//!
+//! To inspect a buffer without a window, print a text preview with `debug_ascii`.
+//! To write a buffer to an image file, pass an `Encoder`, such as `PpmEncoder`, to `export`. Implement `Encoder` to support other formats.
+//!
+//! ## Features
+//!
+//! - `png`: Adds `save_png`, which writes the buffer to a PNG file using [`png`](https://docs.rs/png/latest/png/).
+//! - `rayon`: Adds `par_map_pixels` and `par_shade`, which process rows in parallel using [`rayon`](https://docs.rs/rayon/latest/rayon/).
+//! - `simd`: `fill` writes eight pixels at a time using [`wide`](https://docs.rs/wide/latest/wide/).
+//!
 //! ## Example
 //!
 //! ```rust
 //!use softbuffer::{Context, Surface};
-//!use std::num::NonZeroU32;
 //!use winit::application::ApplicationHandler;
 //!use winit::dpi::LogicalSize;
 //!use winit::event::{StartCause, WindowEvent};
 //!use winit::event_loop::{ActiveEventLoop, EventLoop};
 //!use winit::window::{Window, WindowAttributes, WindowId};
 //!
-//!use softbuffer_rgb::RgbBuffer;
+//!use softbuffer_rgb::RgbSurface;
 //!
 //!const X: usize = 400;
 //!const Y: usize = 300;
@@ -85,18 +112,15 @@
 //!            // Get the window.
 //!            let window = self.window.as_ref().unwrap();
 //!            let context = Context::new(window).unwrap();
-//!            let mut surface = Surface::new(&context, &window).unwrap();
+//!            let mut surface = RgbSurface::<X, Y, _, _>::new(Surface::new(&context, &window).unwrap());
+//!            // Draw and present a frame.
 //!            surface
-//!                .resize(
-//!                    NonZeroU32::new(X as u32).unwrap(),
-//!                    NonZeroU32::new(Y as u32).unwrap(),
-//!                )
+//!                .frame(|rgb_buffer| {
+//!                    let x = 12;
+//!                    let y = 23;
+//!                    rgb_buffer.pixels_mut()[y][x] = [0, 200, 100, 70];
+//!                })
 //!                .unwrap();
-//!            let mut rgb_buffer =
-//!                RgbBuffer::<X, Y, _, _>::from_softbuffer(surface.buffer_mut().unwrap()).unwrap();
-//!            let x = 12;
-//!            let y = 23;
-//!            rgb_buffer.pixels[y][x] = [0, 200, 100, 70];
 //!            event_loop.exit();
 //!        }
 //!    }
@@ -105,29 +129,103 @@
 //!}
 //!```
 
-use std::{fmt, slice};
+use std::num::NonZeroU32;
+use std::{error, fmt, slice};
 
+#[cfg(feature = "png")]
+pub use png;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 pub use softbuffer;
-use softbuffer::Buffer;
+use softbuffer::{Buffer, SoftBufferError, Surface};
 
-#[derive(Debug, Clone)]
-pub struct SizeError {
-    pub x: usize,
-    pub y: usize,
+mod accum;
+mod canvas;
+mod color;
+mod context;
+mod encoder;
+mod font;
+mod line;
+mod marker;
+mod mask;
+mod owned;
+mod rect;
+mod scratch;
+mod sub_view;
+mod surface;
+pub use accum::AccumBuffer;
+pub use canvas::Canvas;
+pub use color::{
+    blend, from_u32, luminance, premultiply, rgb, to_u32, unpremultiply, BlendMode, Channel, Color,
+    ColorFormat, Rgb, BLACK, BLUE, CYAN, GRAY, GREEN, MAGENTA, RED, WHITE, YELLOW,
+};
+pub use context::DrawContext;
+pub use encoder::{Encoder, PpmEncoder};
+pub use line::{line_points, LinePoints};
+pub use marker::MarkerShape;
+pub use mask::Mask;
+pub use owned::OwnedRgbBuffer;
+pub use rect::Rect;
+pub use scratch::Scratch;
+pub use sub_view::RgbSubView;
+pub use surface::RgbSurface;
+
+#[derive(Debug)]
+pub enum RgbBufferError {
+    /// The dimensions `(x, y)` are invalid.
+    /// For methods that take a slice of colors and a width, `x` is the width and `y` is the length of the slice.
+    InvalidSize { x: usize, y: usize },
+    /// The position `(x, y)` is out of bounds.
+    InvalidPosition { x: usize, y: usize },
+    /// The buffer has `actual` pixels, but the dimensions `X` and `Y` require `expected = X * Y` pixels.
+    /// This usually means that the window was resized but the buffer wasn't.
+    SizeMismatch { expected: usize, actual: usize },
+    /// softbuffer returned an error.
+    SoftBuffer(SoftBufferError),
 }
 
-impl fmt::Display for SizeError {
+impl fmt::Display for RgbBufferError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Invalid size: ({0}, {1})", self.x, self.y)
+        match self {
+            Self::InvalidSize { x, y } => write!(f, "Invalid size: ({0}, {1})", x, y),
+            Self::InvalidPosition { x, y } => write!(f, "Invalid position: ({0}, {1})", x, y),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "Size mismatch: expected {0} pixels but got {1}",
+                expected, actual
+            ),
+            Self::SoftBuffer(error) => write!(f, "softbuffer error: {0}", error),
+        }
+    }
+}
+
+impl error::Error for RgbBufferError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::SoftBuffer(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<SoftBufferError> for RgbBufferError {
+    fn from(error: SoftBufferError) -> Self {
+        Self::SoftBuffer(error)
     }
 }
 
-pub type Color = [u8; 4];
+/// The error type that earlier versions returned from `RgbBuffer::from_softbuffer`.
+///
+/// This is now an alias of `RgbBufferError`. It used to be a struct with `x` and `y` fields, which are now in `RgbBufferError::InvalidSize`.
+#[deprecated(note = "use `RgbBufferError` instead")]
+pub type SizeError = RgbBufferError;
 
-/// An `RgbBuffer` contains a softbuffer `buffer` and `pixels`, a mutable slice of the same data.
-/// `buffer` and `pixels` reference the same underlying data.
-/// Modifying the elements of one will affect the values of the other.
+/// An `RgbBuffer` contains a softbuffer `Buffer` and `pixels`, a mutable slice of the same data.
+/// Modifying `pixels_mut()` will modify the underlying `Buffer`.
+///
+/// The `Buffer` and `pixels` are private because two mutable paths to the same memory can't safely be used at the same time.
+/// To access the pixels, call `pixels` or `pixels_mut`, which borrow the `RgbBuffer`.
+/// To access the `Buffer`, call `with_buffer`, which borrows the whole `RgbBuffer` so that `pixels` can't be used concurrently.
+/// To present the `Buffer`, call `present` or `present_with_damage`.
 ///
 /// Color data is represented as a 4-element array where the first element is always 0.
 /// This will align the color data correctly for `softbuffer`.
@@ -138,67 +236,191 @@ pub type Color = [u8; 4];
 /// - `D` and `W` are generics that should match those of the `Buffer<D, W>`.
 pub struct RgbBuffer<'s, const X: usize, const Y: usize, D: HasDisplayHandle, W: HasWindowHandle> {
     /// The "raw" softbuffer `Buffer`.
-    pub buffer: Buffer<'s, D, W>,
+    buffer: Buffer<'s, D, W>,
     /// The "raw" RGB pixel data as a 3D array where the axes are: `Y`, `X`, and 4 (XRGB).
-    /// Note that the order is: `Y, X`. Therefore, to get the pixel at `x=4, y=5`: `self.pixels[y][x]`.
-    /// The color has four elements. The first element should always be 0, and the other three are R, G, and B: `self.pixels[y][x] = [0, 200, 160, 30];`
-    /// This will align the color data correctly for `softbuffer`.
-    ///
-    /// Do not reassign the entire array!
-    /// It's a slice of `self.buffer`.
-    /// If it's something else, something bad might happen!
-    pub pixels: &'s mut [[Color; X]],
+    /// It's a slice of the underlying `Buffer`, so it's private: replacing it would alias the buffer.
+    pixels: &'s mut [[Color; X]],
 }
 
 impl<'s, const X: usize, const Y: usize, D: HasDisplayHandle, W: HasWindowHandle>
     RgbBuffer<'s, X, Y, D, W>
 {
+    /// Returns the "raw" RGB pixel data as a 3D array where the axes are: `Y`, `X`, and 4 (XRGB).
+    /// Note that the order is: `Y, X`. Therefore, to get the pixel at `x=4, y=5`: `self.pixels()[y][x]`.
+    pub fn pixels(&self) -> &[[Color; X]] {
+        self.pixels
+    }
+
+    /// Returns the "raw" RGB pixel data as a mutable 3D array where the axes are: `Y`, `X`, and 4 (XRGB).
+    /// Note that the order is: `Y, X`. Therefore, to set the pixel at `x=4, y=5`: `self.pixels_mut()[y][x] = [0, 200, 160, 30];`
+    /// The color has four elements. The first element should always be 0, and the other three are R, G, and B.
+    /// This will align the color data correctly for `softbuffer`.
+    pub fn pixels_mut(&mut self) -> &mut [[Color; X]] {
+        self.pixels
+    }
+
     /// Convert a `Buffer` into an `RgbBuffer`. This consumes `buffer` and returns an `RgbBuffer`.
     /// This returns an `Error` if `X * Y != buffer.len()` (i.e. if the dimensions of the `RgbBuffer` are invalid).
-    pub fn from_softbuffer(mut buffer: Buffer<'s, D, W>) -> Result<Self, SizeError> {
+    pub fn from_softbuffer(mut buffer: Buffer<'s, D, W>) -> Result<Self, RgbBufferError> {
         // Test whether the dimensions are valid.
         if X * Y != buffer.len() {
-            Err(SizeError { x: X, y: Y })
+            Err(RgbBufferError::SizeMismatch {
+                expected: X * Y,
+                actual: buffer.len(),
+            })
         } else {
-            // Convert the raw buffer to an array of rows.
-            let ptr = buffer.as_mut_ptr() as *mut [Color; X];
-            // Get the 3D pixel array.
-            let pixels = unsafe { slice::from_raw_parts_mut(ptr, Y) };
+            let pixels = Self::get_pixels(&mut buffer);
             Ok(RgbBuffer { buffer, pixels })
         }
     }
 
-    /// Fill the buffer with an `[0, r, g, b]` color.
-    pub fn fill(&mut self, color: Color) {
-        self.pixels.fill([color; X]);
+    /// Convert a `Buffer` into an `RgbBuffer` like `from_softbuffer`, and then fill it with black.
+    ///
+    /// The contents of a softbuffer `Buffer` are undefined after `Surface::buffer_mut`, so they might be a previous frame or noise.
+    /// Use this if the frame doesn't overwrite every pixel.
+    /// This returns an `Error` if `X * Y != buffer.len()` (i.e. if the dimensions of the `RgbBuffer` are invalid).
+    pub fn from_softbuffer_zeroed(mut buffer: Buffer<'s, D, W>) -> Result<Self, RgbBufferError> {
+        if X * Y == buffer.len() {
+            buffer.fill(0);
+        }
+        Self::from_softbuffer(buffer)
     }
 
-    /// Set the color of multiple pixels.
+    /// Get scoped, exclusive access to the underlying softbuffer `Buffer`, and return the result of `f`.
     ///
-    /// - `positions`: A slice of `[x, y]` positions.
-    /// - `color`: The `[0, r, g, b]` color.
+    /// The `Buffer` and `pixels` reference the same data, so they shouldn't be used at the same time.
+    /// This borrows the whole `RgbBuffer` while `f` runs, so `pixels` can't be used until `f` returns.
+    /// Afterwards, `pixels` is derived again from the buffer.
     ///
-    /// Panics if any position in `positions` is out of bounds.
-    pub fn set_pixels(&mut self, positions: &[[usize; 2]], color: Color) {
-        // Copy the color into each position.
-        for position in positions {
-            self.pixels[position[1]][position[0]] = color;
-        }
+    /// Panics if `f` replaces the buffer with one whose length isn't `X * Y`.
+    pub fn with_buffer<R>(&mut self, f: impl FnOnce(&mut Buffer<'s, D, W>) -> R) -> R {
+        let result = f(&mut self.buffer);
+        assert_eq!(self.buffer.len(), X * Y, "The buffer was resized");
+        self.pixels = Self::get_pixels(&mut self.buffer);
+        result
+    }
+
+    /// Fill the buffer with a color that has already been converted to a softbuffer u32, e.g. with `to_u32`.
+    /// This writes directly to the underlying `Buffer`.
+    pub fn fill_u32(&mut self, color: u32) {
+        self.with_buffer(|buffer| buffer.fill(color));
     }
 
-    /// Fill a rectangle with a color.
+    /// Fill a rectangle with a color that has already been converted to a softbuffer u32, e.g. with `to_u32`.
+    /// This writes directly to the underlying `Buffer`.
     ///
     /// - `x` and `y` are the coordinates of the top-left pixel.
     /// - `w` and `h` are the width and height of the rectangle.
-    /// - `color` is the `[0, r, g, b]` color.
+    /// - `color` is the u32 color.
     ///
     /// Panics if the top-left or bottom-right positions are out of bounds.
-    pub fn fill_rectangle(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color) {
-        // Create a row of colors and get a slice of it.
-        let colors = &[color; Y][x..x + w];
-        // Fill the rectangle.
-        self.pixels[y..y + h]
-            .iter_mut()
-            .for_each(|cols| cols[x..x + w].copy_from_slice(colors));
+    pub fn fill_rectangle_u32(&mut self, x: usize, y: usize, w: usize, h: usize, color: u32) {
+        self.with_buffer(|buffer| {
+            buffer[y * X..(y + h) * X]
+                .chunks_exact_mut(X)
+                .for_each(|cols| cols[x..x + w].fill(color))
+        });
+    }
+
+    /// Fill a rectangle with black by writing zeros directly to the underlying `Buffer`, e.g. to erase a region before redrawing it.
+    ///
+    /// - `x` and `y` are the coordinates of the top-left pixel.
+    /// - `w` and `h` are the width and height of the rectangle.
+    ///
+    /// The rectangle is clipped to the buffer.
+    pub fn clear_rectangle(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        let rect = Rect::new(x, y, w, h).clamp_to(X, Y);
+        self.with_buffer(|buffer| {
+            buffer[rect.y * X..(rect.y + rect.h) * X]
+                .chunks_exact_mut(X)
+                .for_each(|cols| cols[rect.x..rect.x + rect.w].fill(0))
+        });
+    }
+
+    /// Returns the age of the underlying `Buffer`. See `softbuffer::Buffer::age`.
+    pub fn age(&self) -> u8 {
+        self.buffer.age()
+    }
+
+    /// Present the buffer to the window. This consumes the `RgbBuffer`.
+    pub fn present(self) -> Result<(), SoftBufferError> {
+        self.buffer.present()
+    }
+
+    /// Present the buffer to the window, but only update the `damage` regions. This consumes the `RgbBuffer`.
+    /// See `softbuffer::Buffer::present_with_damage`.
+    pub fn present_with_damage(self, damage: &[softbuffer::Rect]) -> Result<(), SoftBufferError> {
+        self.buffer.present_with_damage(damage)
+    }
+
+    /// Present the buffer to the window, but only update `rect`. This consumes the `RgbBuffer`.
+    /// This is a shortcut for `present_with_damage` with a single damage rectangle.
+    ///
+    /// `rect` is clipped to the buffer. If the clipped rectangle is empty, nothing is updated.
+    pub fn present_region(self, rect: Rect) -> Result<(), SoftBufferError> {
+        let rect = rect.clamp_to(X, Y);
+        match (
+            NonZeroU32::new(rect.w as u32),
+            NonZeroU32::new(rect.h as u32),
+        ) {
+            (Some(width), Some(height)) => self.buffer.present_with_damage(&[softbuffer::Rect {
+                x: rect.x as u32,
+                y: rect.y as u32,
+                width,
+                height,
+            }]),
+            _ => self.buffer.present_with_damage(&[]),
+        }
+    }
+
+    /// Resize `surface` to `X` by `Y` and convert its buffer into an `RgbBuffer`.
+    /// This is a shortcut for `surface.resize(X, Y)` followed by `from_softbuffer(surface.buffer_mut())`.
+    ///
+    /// Call this every frame, including after the window is resized: the size of the `RgbBuffer` can't change at runtime, so this resizes the surface back to `X` by `Y`.
+    /// Resizing a surface to its current size is cheap.
+    ///
+    /// This returns an `Error` if `X` or `Y` is zero or too large, or if softbuffer fails to resize the surface or get the buffer.
+    pub fn from_surface(surface: &'s mut Surface<D, W>) -> Result<Self, RgbBufferError> {
+        let size = |v: usize| u32::try_from(v).ok().and_then(NonZeroU32::new);
+        match (size(X), size(Y)) {
+            (Some(width), Some(height)) => {
+                surface.resize(width, height)?;
+                Self::from_softbuffer(surface.buffer_mut()?)
+            }
+            _ => Err(RgbBufferError::InvalidSize { x: X, y: Y }),
+        }
+    }
+
+    /// Reinterpret the buffer's memory as rows of pixels.
+    /// `buffer.len()` must be `X * Y`.
+    fn get_pixels(buffer: &mut Buffer<'s, D, W>) -> &'s mut [[Color; X]] {
+        // Convert the raw buffer to an array of rows.
+        let ptr = buffer.as_mut_ptr() as *mut [Color; X];
+        // Get the 3D pixel array.
+        unsafe { slice::from_raw_parts_mut(ptr, Y) }
+    }
+}
+
+impl<const X: usize, const Y: usize, D: HasDisplayHandle, W: HasWindowHandle> Canvas<X, Y>
+    for RgbBuffer<'_, X, Y, D, W>
+{
+    fn pixels(&self) -> &[[Color; X]] {
+        self.pixels
+    }
+
+    fn pixels_mut(&mut self) -> &mut [[Color; X]] {
+        self.pixels
+    }
+}
+
+impl<const X: usize, const Y: usize, D: HasDisplayHandle, W: HasWindowHandle> fmt::Debug
+    for RgbBuffer<'_, X, Y, D, W>
+{
+    /// Only the dimensions are shown. To preview the pixels, use `debug_ascii`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RgbBuffer")
+            .field("width", &X)
+            .field("height", &Y)
+            .finish_non_exhaustive()
     }
 }
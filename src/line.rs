@@ -0,0 +1,155 @@
+/// Returns an iterator over the points of a 1-pixel line from `(x0, y0)` to `(x1, y1)`, using Bresenham's line algorithm.
+///
+/// These are the pixels that `Canvas::draw_line` would draw, including both endpoints, but no colors are written.
+/// `draw_line` skips the points that are out of bounds without changing the others.
+/// Use this for hit-testing or custom per-pixel rendering.
+/// The points aren't clipped to a buffer, so they can be negative.
+pub fn line_points(x0: isize, y0: isize, x1: isize, y1: isize) -> LinePoints {
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    LinePoints {
+        x0,
+        y0,
+        x: x0,
+        y: y0,
+        x1,
+        y1,
+        dx,
+        dy,
+        sx: if x0 < x1 { 1 } else { -1 },
+        sy: if y0 < y1 { 1 } else { -1 },
+        error: dx + dy,
+        done: false,
+    }
+}
+
+/// An iterator over the `(x, y)` points of a line. See `line_points`.
+#[derive(Debug, Clone)]
+pub struct LinePoints {
+    /// The coordinates of the start of the line.
+    x0: isize,
+    y0: isize,
+    /// The coordinates of the next point.
+    x: isize,
+    y: isize,
+    /// The coordinates of the end of the line.
+    x1: isize,
+    y1: isize,
+    /// The absolute distances along each axis. `dy` is negative.
+    dx: isize,
+    dy: isize,
+    /// The direction of each step along each axis.
+    sx: isize,
+    sy: isize,
+    /// The accumulated error.
+    error: isize,
+    /// If true, the end of the line has been returned.
+    done: bool,
+}
+
+impl LinePoints {
+    /// Skip the points that are outside of a `width` by `height` area, and stop at the last point that is inside of it.
+    /// Unlike clipping the endpoints and drawing a new line between them, this doesn't change which pixels the line steps through.
+    pub(crate) fn clip(mut self, width: usize, height: usize) -> Self {
+        let (w, h) = (width as isize, height as isize);
+        let (sx, sy) = (self.sx, self.sy);
+        // Each coordinate moves in only one direction, so a point is either before the area, inside of it, or after it.
+        let before = |(x, y): (isize, isize)| {
+            (sx > 0 && x < 0) || (sx < 0 && x >= w) || (sy > 0 && y < 0) || (sy < 0 && y >= h)
+        };
+        let after = |(x, y): (isize, isize)| {
+            (sx > 0 && x >= w) || (sx < 0 && x < 0) || (sy > 0 && y >= h) || (sy < 0 && y < 0)
+        };
+        let steps = self.steps();
+        let first = partition_point(steps, |k| before(self.point_at(k)));
+        let end = partition_point(steps, |k| !after(self.point_at(k)));
+        if first >= end {
+            self.done = true;
+        } else {
+            (self.x1, self.y1) = self.point_at(end - 1);
+            self.seek(first);
+        }
+        self
+    }
+
+    /// Returns the number of steps from the start of the line to the next point, e.g. how many points `clip` skipped.
+    pub(crate) fn position(&self) -> u128 {
+        let nx = (self.x as i128 - self.x0 as i128).unsigned_abs();
+        let ny = (self.y as i128 - self.y0 as i128).unsigned_abs();
+        nx.max(ny)
+    }
+
+    /// Returns the number of steps from the start to the end of the line.
+    fn steps(&self) -> i128 {
+        self.dx.max(-self.dy) as i128
+    }
+
+    /// Returns the number of steps along each axis after `k` steps along the line.
+    fn axis_steps(&self, k: i128) -> (i128, i128) {
+        let (dx, dy) = (self.dx as i128, -self.dy as i128);
+        if dx == 0 && dy == 0 {
+            (0, 0)
+        } else if dx >= dy {
+            // The x axis steps every time. The y axis steps when the error passes the midpoint.
+            (k, (2 * dy * k + dx) / (2 * dx))
+        } else {
+            ((2 * dx * k + dy) / (2 * dy), k)
+        }
+    }
+
+    /// Returns the point after `k` steps along the line.
+    fn point_at(&self, k: i128) -> (isize, isize) {
+        let (nx, ny) = self.axis_steps(k);
+        (
+            self.x0 + nx as isize * self.sx,
+            self.y0 + ny as isize * self.sy,
+        )
+    }
+
+    /// Move to the point after `k` steps along the line, as if `next` had been called `k` times.
+    fn seek(&mut self, k: i128) {
+        let (nx, ny) = self.axis_steps(k);
+        (self.x, self.y) = self.point_at(k);
+        // The error is `dx + dy`, plus `dy` for every x step and `dx` for every y step.
+        self.error = (self.dx as i128 * (1 + ny) + self.dy as i128 * (1 + nx)) as isize;
+    }
+}
+
+/// Returns the first `k` in `0..=steps` for which `f` is false, or `steps + 1`. `f` must be true for a prefix of the range.
+pub(crate) fn partition_point(steps: i128, f: impl Fn(i128) -> bool) -> i128 {
+    let (mut lo, mut hi) = (0, steps + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if f(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+impl Iterator for LinePoints {
+    type Item = (isize, isize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let point = (self.x, self.y);
+        if self.x == self.x1 && self.y == self.y1 {
+            self.done = true;
+        } else {
+            let e2 = 2 * self.error;
+            if e2 >= self.dy {
+                self.error += self.dy;
+                self.x += self.sx;
+            }
+            if e2 <= self.dx {
+                self.error += self.dx;
+                self.y += self.sy;
+            }
+        }
+        Some(point)
+    }
+}
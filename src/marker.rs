@@ -0,0 +1,14 @@
+/// The shape of a marker drawn by `Canvas::draw_marker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkerShape {
+    /// An `x`: two diagonal lines.
+    Cross,
+    /// A `+`: a horizontal line and a vertical line.
+    Plus,
+    /// The outline of a square.
+    Square,
+    /// The outline of a circle.
+    Circle,
+    /// The outline of a square rotated by 45 degrees.
+    Diamond,
+}
@@ -0,0 +1,42 @@
+/// An `X` by `Y` 1-bit mask, e.g. a stencil for `Canvas::blit_masked`.
+/// Each pixel is one bit, so this is much smaller than a buffer of colors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mask<const X: usize, const Y: usize> {
+    /// The bits, row by row. Bit `i % 64` of `bits[i / 64]` is the pixel at index `i = y * X + x`.
+    bits: Box<[u64]>,
+}
+
+impl<const X: usize, const Y: usize> Mask<X, Y> {
+    /// Returns a new mask where every bit is clear.
+    pub fn new() -> Self {
+        Self {
+            bits: vec![0; (X * Y).div_ceil(64)].into_boxed_slice(),
+        }
+    }
+
+    /// Returns true if the bit at `(x, y)` is set, or false if the position is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < X && y < Y && {
+            let i = y * X + x;
+            self.bits[i / 64] & (1 << (i % 64)) != 0
+        }
+    }
+
+    /// Set or clear the bit at `(x, y)`. If the position is out of bounds, this does nothing.
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        if x < X && y < Y {
+            let i = y * X + x;
+            if value {
+                self.bits[i / 64] |= 1 << (i % 64);
+            } else {
+                self.bits[i / 64] &= !(1 << (i % 64));
+            }
+        }
+    }
+}
+
+impl<const X: usize, const Y: usize> Default for Mask<X, Y> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{from_u32, to_u32, Canvas, Color, RgbBufferError};
+
+/// An `OwnedRgbBuffer` is an `X` by `Y` pixel buffer that owns its data instead of wrapping a softbuffer `Buffer`.
+/// It doesn't need a window, so it's useful for offscreen rendering and headless tests.
+///
+/// It has the same drawing methods as `RgbBuffer` via the `Canvas` trait.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedRgbBuffer<const X: usize, const Y: usize> {
+    pixels: Box<[[Color; X]]>,
+}
+
+impl<const X: usize, const Y: usize> OwnedRgbBuffer<X, Y> {
+    /// Returns the RGB pixel data as `Y` rows of `X` colors. To get the pixel at `x=4, y=5`: `self.pixels()[y][x]`.
+    pub fn pixels(&self) -> &[[Color; X]] {
+        &self.pixels
+    }
+
+    /// Returns the RGB pixel data as `Y` mutable rows of `X` colors. To set the pixel at `x=4, y=5`: `self.pixels_mut()[y][x] = [0, 200, 160, 30];`
+    pub fn pixels_mut(&mut self) -> &mut [[Color; X]] {
+        &mut self.pixels
+    }
+
+    /// Returns a new buffer where every pixel is black.
+    pub fn new() -> Self {
+        Self {
+            pixels: vec![[[0; 4]; X]; Y].into_boxed_slice(),
+        }
+    }
+
+    /// Convert a vec of softbuffer-style `0RGB` u32s into an `OwnedRgbBuffer`.
+    /// This returns an `Error` if `X * Y != buffer.len()` (i.e. if the dimensions of the `OwnedRgbBuffer` are invalid).
+    pub fn from_vec(buffer: Vec<u32>) -> Result<Self, RgbBufferError> {
+        if X * Y != buffer.len() {
+            Err(RgbBufferError::SizeMismatch {
+                expected: X * Y,
+                actual: buffer.len(),
+            })
+        } else {
+            let pixels = buffer
+                .chunks_exact(X)
+                .map(|row| std::array::from_fn(|x| from_u32(row[x])))
+                .collect();
+            Ok(Self { pixels })
+        }
+    }
+
+    /// Convert the pixels into a vec of softbuffer-style `0RGB` u32s.
+    pub fn to_vec(&self) -> Vec<u32> {
+        self.pixels
+            .iter()
+            .flat_map(|cols| cols.iter().map(|c| to_u32(*c)))
+            .collect()
+    }
+}
+
+impl<const X: usize, const Y: usize> Default for OwnedRgbBuffer<X, Y> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const X: usize, const Y: usize> Canvas<X, Y> for OwnedRgbBuffer<X, Y> {
+    fn pixels(&self) -> &[[Color; X]] {
+        &self.pixels
+    }
+
+    fn pixels_mut(&mut self) -> &mut [[Color; X]] {
+        &mut self.pixels
+    }
+}
@@ -0,0 +1,56 @@
+/// A rectangle defined by the coordinates of its top-left pixel and its size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The x coordinate of the top-left pixel.
+    pub x: usize,
+    /// The y coordinate of the top-left pixel.
+    pub y: usize,
+    /// The width of the rectangle.
+    pub w: usize,
+    /// The height of the rectangle.
+    pub h: usize,
+}
+
+impl Rect {
+    /// Returns a new rectangle.
+    pub const fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// Returns true if the rectangle has no area.
+    pub const fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    /// Returns true if the pixel at `(x, y)` is inside the rectangle.
+    pub const fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.w && y - self.y < self.h
+    }
+
+    /// Returns the area shared by this rectangle and `other`, or `None` if they don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = self
+            .x
+            .saturating_add(self.w)
+            .min(other.x.saturating_add(other.w));
+        let y1 = self
+            .y
+            .saturating_add(self.h)
+            .min(other.y.saturating_add(other.h));
+        if x0 < x1 && y0 < y1 {
+            Some(Rect::new(x0, y0, x1 - x0, y1 - y0))
+        } else {
+            None
+        }
+    }
+
+    /// Clip the rectangle such that it fits inside a `width` by `height` area.
+    /// The returned rectangle might be empty.
+    pub fn clamp_to(&self, width: usize, height: usize) -> Rect {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        Rect::new(x, y, self.w.min(width - x), self.h.min(height - y))
+    }
+}
@@ -0,0 +1,29 @@
+use crate::Color;
+
+/// A reusable `X` by `Y` buffer of colors for effects such as `Canvas::blur_with_scratch`.
+/// Effects that need temporary storage allocate it on every call. To avoid allocating every frame, create a `Scratch` once and pass it to the `_with_scratch` variants.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scratch<const X: usize, const Y: usize> {
+    /// The colors, row by row. The contents are only meaningful during an effect.
+    colors: Box<[Color]>,
+}
+
+impl<const X: usize, const Y: usize> Scratch<X, Y> {
+    /// Returns a new scratch buffer. This allocates `X * Y` colors.
+    pub fn new() -> Self {
+        Self {
+            colors: vec![[0; 4]; X * Y].into_boxed_slice(),
+        }
+    }
+
+    /// Returns the colors, row by row.
+    pub(crate) fn colors_mut(&mut self) -> &mut [Color] {
+        &mut self.colors
+    }
+}
+
+impl<const X: usize, const Y: usize> Default for Scratch<X, Y> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
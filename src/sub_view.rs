@@ -0,0 +1,74 @@
+use crate::{Color, Rect};
+
+/// A mutable view of a rectangular region of a `Canvas`, such as an `RgbBuffer`. See `Canvas::region_mut`.
+///
+/// Coordinates are local to the region: `(0, 0)` is the region's top-left pixel.
+/// Drawing is clipped to the region.
+pub struct RgbSubView<'a, const X: usize> {
+    /// The rows of the buffer that the region spans.
+    rows: &'a mut [[Color; X]],
+    /// The x coordinate of the region's left column in the buffer.
+    x: usize,
+    /// The width of the region.
+    w: usize,
+}
+
+impl<'a, const X: usize> RgbSubView<'a, X> {
+    /// `rect` must already be clamped to the buffer.
+    pub(crate) fn new(pixels: &'a mut [[Color; X]], rect: Rect) -> Self {
+        Self {
+            rows: &mut pixels[rect.y..rect.y + rect.h],
+            x: rect.x,
+            w: rect.w,
+        }
+    }
+
+    /// Returns the width of the region.
+    pub const fn width(&self) -> usize {
+        self.w
+    }
+
+    /// Returns the height of the region.
+    pub const fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the color of the pixel at `(x, y)`, or `None` if the position is outside of the region.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x < self.w {
+            self.rows.get(y).map(|cols| cols[self.x + x])
+        } else {
+            None
+        }
+    }
+
+    /// Set the color of the pixel at `(x, y)`. If the position is outside of the region, this does nothing.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.w {
+            if let Some(cols) = self.rows.get_mut(y) {
+                cols[self.x + x] = color;
+            }
+        }
+    }
+
+    /// Fill the region with an `[0, r, g, b]` color.
+    pub fn fill(&mut self, color: Color) {
+        let (x0, x1) = (self.x, self.x + self.w);
+        self.rows
+            .iter_mut()
+            .for_each(|cols| cols[x0..x1].fill(color));
+    }
+
+    /// Fill a rectangle with a color. The rectangle is clipped to the region.
+    ///
+    /// - `rect` is the rectangle in local coordinates.
+    /// - `color` is the `[0, r, g, b]` color.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rect = rect.clamp_to(self.w, self.rows.len());
+        let x0 = self.x + rect.x;
+        let x1 = x0 + rect.w;
+        self.rows[rect.y..rect.y + rect.h]
+            .iter_mut()
+            .for_each(|cols| cols[x0..x1].fill(color));
+    }
+}
@@ -0,0 +1,51 @@
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use softbuffer::Surface;
+
+use crate::{RgbBuffer, RgbBufferError};
+
+/// An `RgbSurface` owns a softbuffer `Surface` and creates and presents an `RgbBuffer` every frame.
+///
+/// `RgbBuffer::present` consumes the `RgbBuffer`, so each frame needs a new one.
+/// `frame` does all of that: it resizes the surface to `X` by `Y`, gets the buffer, runs a draw function, and presents the buffer.
+pub struct RgbSurface<const X: usize, const Y: usize, D: HasDisplayHandle, W: HasWindowHandle> {
+    surface: Surface<D, W>,
+}
+
+impl<const X: usize, const Y: usize, D: HasDisplayHandle, W: HasWindowHandle>
+    RgbSurface<X, Y, D, W>
+{
+    /// Wrap a softbuffer `Surface`. The surface is resized to `X` by `Y` at the start of each frame.
+    pub fn new(surface: Surface<D, W>) -> Self {
+        Self { surface }
+    }
+
+    /// Draw and present a frame.
+    ///
+    /// `draw` is called with a new `RgbBuffer`. Afterwards, the buffer is presented.
+    ///
+    /// This returns an `Error` if `RgbBuffer::from_surface` fails or if softbuffer fails to present the buffer.
+    pub fn frame(
+        &mut self,
+        draw: impl FnOnce(&mut RgbBuffer<'_, X, Y, D, W>),
+    ) -> Result<(), RgbBufferError> {
+        let mut rgb_buffer = RgbBuffer::from_surface(&mut self.surface)?;
+        draw(&mut rgb_buffer);
+        rgb_buffer.present()?;
+        Ok(())
+    }
+
+    /// Returns the underlying softbuffer `Surface`.
+    pub fn surface(&self) -> &Surface<D, W> {
+        &self.surface
+    }
+
+    /// Returns the underlying softbuffer `Surface`.
+    pub fn surface_mut(&mut self) -> &mut Surface<D, W> {
+        &mut self.surface
+    }
+
+    /// Unwrap the underlying softbuffer `Surface`.
+    pub fn into_inner(self) -> Surface<D, W> {
+        self.surface
+    }
+}
@@ -0,0 +1,1919 @@
+use softbuffer_rgb::{
+    from_u32, luminance, rgb, to_u32, AccumBuffer, BlendMode, Canvas, Color, ColorFormat,
+    MarkerShape, Mask, OwnedRgbBuffer, PpmEncoder, Rect, Rgb, RgbBufferError, Scratch, BLACK, BLUE,
+    GRAY, GREEN, MAGENTA, RED, WHITE,
+};
+
+const X: usize = 40;
+const Y: usize = 30;
+
+#[test]
+fn from_vec() {
+    assert!(matches!(
+        OwnedRgbBuffer::<X, Y>::from_vec(vec![0; X * Y - 1]),
+        Err(RgbBufferError::SizeMismatch { expected, actual }) if expected == X * Y && actual == X * Y - 1
+    ));
+    assert_eq!(
+        RgbBufferError::SizeMismatch {
+            expected: 12,
+            actual: 6
+        }
+        .to_string(),
+        "Size mismatch: expected 12 pixels but got 6"
+    );
+    // Convert to and from u32s.
+    let color = [0, 255, 20, 5];
+    let mut buffer = vec![0; X * Y];
+    buffer[Y / 2 * X + 3] = u32::from_le_bytes(color);
+    let rgb_buffer = OwnedRgbBuffer::<X, Y>::from_vec(buffer.clone()).unwrap();
+    assert_eq!(rgb_buffer.pixels()[Y / 2][3], color);
+    assert_eq!(rgb_buffer.to_vec(), buffer);
+}
+
+#[test]
+fn draw() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let color = [0, 67, 200, 80];
+    rgb_buffer.fill_rect(Rect::new(2, 3, 4, 5), color);
+    assert_eq!(rgb_buffer.count_color(color), 20);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(2, 3, 4, 5)));
+    rgb_buffer.fill(color);
+    assert_eq!(rgb_buffer.count_color(color), X * Y);
+}
+
+#[test]
+fn primitives() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    assert_eq!((rgb_buffer.width(), rgb_buffer.height()), (X, Y));
+    assert_eq!(rgb_buffer.dimensions(), (X, Y));
+    assert!(rgb_buffer.contains(X - 1, Y - 1));
+    assert!(!rgb_buffer.contains(X, 0));
+    assert!(rgb_buffer.contains_rect(Rect::new(X - 4, Y - 3, 4, 3)));
+    assert!(!rgb_buffer.contains_rect(Rect::new(X - 4, Y - 3, 5, 3)));
+    assert!(!rgb_buffer.contains_rect(Rect::new(1, 0, usize::MAX, 1)));
+    let color = [0, 255, 20, 5];
+    unsafe { rgb_buffer.set_pixel_unchecked(X - 1, Y - 1, color) };
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], color);
+    assert_eq!(unsafe { rgb_buffer.pixel_unchecked(X - 1, Y - 1) }, color);
+    // Draw a diagonal line.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line(3, 2, 13, 12, color);
+    assert_eq!(rgb_buffer.count_color(color), 11);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(3, 2, 11, 11))
+    );
+    // Draw a line that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line(X - 5, 0, X + 5, 0, color);
+    assert_eq!(rgb_buffer.count_color(color), 5);
+    // Fill a circle that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_circle(10, 10, 5, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(5, 5, 11, 11))
+    );
+    rgb_buffer.fill_circle(X - 1, 0, 5, color);
+    assert_eq!(rgb_buffer.pixels()[0][X - 6], color);
+    assert_eq!(rgb_buffer.pixels()[5][X - 1], color);
+}
+
+#[test]
+fn blend_rectangle() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill([0, 200, 100, 0]);
+    // Blend a rectangle that is partially out of bounds.
+    rgb_buffer.blend_rectangle(X - 2, Y - 2, 10, 10, [0, 0, 0, 255], 128);
+    assert_eq!(rgb_buffer.count_color([0, 100, 50, 128]), 4);
+    rgb_buffer.blend_pixel(0, 0, [0, 0, 0, 0], 255);
+    assert_eq!(rgb_buffer.pixels()[0][0], [0, 0, 0, 0]);
+    rgb_buffer.blend_pixel(1, 0, [0, 0, 0, 0], 0);
+    assert_eq!(rgb_buffer.pixels()[0][1], [0, 200, 100, 0]);
+}
+
+#[test]
+fn add_rectangle() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill([0, 200, 100, 0]);
+    // Add a rectangle that is partially out of bounds.
+    rgb_buffer.add_rectangle(X - 2, Y - 2, 10, 10, [0, 100, 100, 5]);
+    assert_eq!(rgb_buffer.count_color([0, 255, 200, 5]), 4);
+    // Overlapping draws get brighter.
+    rgb_buffer.add_pixel(0, 0, [0, 0, 50, 5]);
+    rgb_buffer.add_pixel(0, 0, [0, 0, 50, 5]);
+    assert_eq!(rgb_buffer.pixels()[0][0], [0, 200, 200, 10]);
+    // The first byte stays 0.
+    rgb_buffer.add_pixel(1, 0, [9, 0, 0, 0]);
+    assert_eq!(rgb_buffer.pixels()[0][1], [0, 200, 100, 0]);
+    // Out of bounds.
+    rgb_buffer.add_pixel(X, 0, [0, 1, 1, 1]);
+    assert_eq!(rgb_buffer.count_color([0, 200, 100, 0]), X * Y - 5);
+}
+
+#[test]
+fn draw_arc() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let color = [0, 255, 20, 5];
+    // The bottom-right quarter.
+    rgb_buffer.draw_arc(20, 15, 5, 0.0, 90.0, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(20, 15, 6, 6))
+    );
+    // The right half, wrapping past 360.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_arc(20, 15, 5, 270.0, 450.0, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(20, 10, 6, 11))
+    );
+    // The left half. The arc goes clockwise from 90 to 270.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_arc(20, 15, 5, 450.0, 270.0, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(15, 10, 6, 11))
+    );
+    // The whole circle is the union of both halves.
+    let halves = rgb_buffer.count_color(color);
+    rgb_buffer.draw_arc(20, 15, 5, 270.0, 450.0, color);
+    let both = rgb_buffer.count_color(color);
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_arc(20, 15, 5, 0.0, 360.0, color);
+    assert!(both > halves);
+    assert_eq!(rgb_buffer.count_color(color), both);
+    // Draw an arc that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_arc(0, 0, 5, -90.0, 180.0, color);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(0, 0, 6, 6)));
+}
+
+#[test]
+fn fill_gradient_radial() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let inner = [0, 255, 255, 255];
+    let outer = [0, 0, 0, 0];
+    rgb_buffer.fill_gradient_radial(20, 15, 10, inner, outer);
+    assert_eq!(rgb_buffer.pixels()[15][20], inner);
+    assert_eq!(rgb_buffer.pixels()[15][25], [0, 127, 127, 127]);
+    assert_eq!(rgb_buffer.pixels()[15][30], outer);
+    assert_eq!(rgb_buffer.pixels()[0][0], outer);
+    // The gradient is symmetric.
+    assert_eq!(rgb_buffer.pixels()[12][17], rgb_buffer.pixels()[18][23]);
+    // A radius of 0 fills the buffer with `outer`.
+    rgb_buffer.fill_gradient_radial(20, 15, 0, inner, outer);
+    assert_eq!(rgb_buffer.count_color(outer), X * Y);
+    // The center can be out of bounds.
+    rgb_buffer.fill_gradient_radial(X + 5, 15, 10, inner, outer);
+    assert_eq!(rgb_buffer.pixels()[15][X - 1], [0, 102, 102, 102]);
+    assert_eq!(rgb_buffer.pixels()[15][X - 15], outer);
+}
+
+#[test]
+fn threshold() {
+    assert_eq!(luminance([0, 0, 0, 0]), 0);
+    assert_eq!(luminance([0, 255, 255, 255]), 255);
+    assert_eq!(luminance([0, 0, 255, 0]), 149);
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_rect(Rect::new(0, 0, 10, 10), [0, 0, 255, 0]);
+    rgb_buffer.fill_rect(Rect::new(10, 0, 10, 10), [0, 0, 0, 255]);
+    let below = [0, 1, 1, 1];
+    let above = [0, 2, 2, 2];
+    rgb_buffer.threshold(100, below, above);
+    assert_eq!(rgb_buffer.count_color(above), 100);
+    assert_eq!(
+        rgb_buffer.bounding_box(above),
+        Some(Rect::new(0, 0, 10, 10))
+    );
+    assert_eq!(rgb_buffer.count_color(below), X * Y - 100);
+}
+
+#[test]
+fn blur() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let color = [0, 255, 20, 5];
+    // A uniform buffer doesn't change, even at the edges.
+    rgb_buffer.fill(color);
+    rgb_buffer.blur(3);
+    assert_eq!(rgb_buffer.count_color(color), X * Y);
+    rgb_buffer.blur(1000);
+    assert_eq!(rgb_buffer.count_color(color), X * Y);
+    // A single pixel spreads out into a box.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.pixels_mut()[10][10] = [0, 255, 255, 255];
+    rgb_buffer.blur(1);
+    assert_eq!(rgb_buffer.count_color([0, 28, 28, 28]), 9);
+    assert_eq!(
+        rgb_buffer.bounding_box([0, 28, 28, 28]),
+        Some(Rect::new(9, 9, 3, 3))
+    );
+    // Edge pixels are repeated beyond the edge of the buffer.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.pixels_mut()[0][0] = [0, 255, 255, 255];
+    rgb_buffer.blur(1);
+    assert_eq!(rgb_buffer.pixels()[0][0], [0, 113, 113, 113]);
+    assert_eq!(rgb_buffer.pixels()[1][1], [0, 28, 28, 28]);
+}
+
+#[test]
+fn blur_with_scratch() {
+    let mut scratch = Scratch::<X, Y>::new();
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| rgb((x * 6) as u8, (y * 8) as u8, (x ^ y) as u8));
+    let mut expected = rgb_buffer.clone();
+    expected.blur(2);
+    rgb_buffer.blur_with_scratch(2, &mut scratch);
+    assert_eq!(rgb_buffer, expected);
+    // Reuse the scratch buffer.
+    expected.blur(1);
+    rgb_buffer.blur_with_scratch(1, &mut scratch);
+    assert_eq!(rgb_buffer, expected);
+}
+
+#[test]
+fn copy_from() {
+    let mut src = OwnedRgbBuffer::<X, Y>::new();
+    let color = [0, 255, 20, 5];
+    src.fill_rect(Rect::new(2, 3, 4, 5), color);
+    let mut dst = OwnedRgbBuffer::<X, Y>::new();
+    dst.fill([0, 1, 2, 3]);
+    dst.copy_from(&src);
+    assert_eq!(dst, src);
+}
+
+#[test]
+fn dither_ordered() {
+    // Half of a mid-gray tile becomes white.
+    let mut rgb_buffer = OwnedRgbBuffer::<4, 4>::new();
+    rgb_buffer.fill([0, 128, 128, 128]);
+    rgb_buffer.dither_ordered(2);
+    assert_eq!(rgb_buffer.count_color([0, 255, 255, 255]), 8);
+    assert_eq!(rgb_buffer.count_color([0, 0, 0, 0]), 8);
+    // Every channel is quantized to one of the levels.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_gradient_radial(20, 15, 20, [0, 255, 255, 255], [0, 0, 0, 0]);
+    let original = rgb_buffer.clone();
+    rgb_buffer.dither_ordered(4);
+    assert!(rgb_buffer
+        .pixels()
+        .iter()
+        .flatten()
+        .all(|c| c[1..].iter().all(|v| [0, 85, 170, 255].contains(v))));
+    // Colors that are already on a level don't change.
+    assert_eq!(rgb_buffer.pixels()[15][20], [0, 255, 255, 255]);
+    assert_eq!(rgb_buffer.pixels()[0][0], [0, 0, 0, 0]);
+    // Fewer than 2 levels don't change anything.
+    let mut rgb_buffer = original.clone();
+    rgb_buffer.dither_ordered(1);
+    assert_eq!(rgb_buffer, original);
+}
+
+#[test]
+fn replace_colors() {
+    let a = [0, 1, 1, 1];
+    let b = [0, 2, 2, 2];
+    let c = [0, 3, 3, 3];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(a);
+    rgb_buffer.fill_rect(Rect::new(0, 0, 4, 5), b);
+    rgb_buffer.replace_color(a, c);
+    assert_eq!(rgb_buffer.count_color(c), X * Y - 20);
+    assert_eq!(rgb_buffer.count_color(b), 20);
+    // Swap two colors.
+    rgb_buffer.replace_colors(&[(b, c), (c, b), (c, a)]);
+    assert_eq!(rgb_buffer.count_color(b), X * Y - 20);
+    assert_eq!(rgb_buffer.count_color(c), 20);
+    assert_eq!(rgb_buffer.count_color(a), 0);
+}
+
+#[test]
+fn crop_into() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let color = [0, 255, 20, 5];
+    rgb_buffer.pixels_mut()[Y - 1][X - 1] = color;
+    let mut crop = OwnedRgbBuffer::<4, 3>::new();
+    rgb_buffer.crop_into(X - 4, Y - 3, &mut crop).unwrap();
+    assert_eq!(crop.pixels()[2][3], color);
+    assert_eq!(crop.count_color(color), 1);
+    assert!(matches!(
+        rgb_buffer.crop_into(X - 3, 0, &mut crop),
+        Err(RgbBufferError::InvalidPosition { x, y: 0 }) if x == X - 3
+    ));
+}
+
+#[test]
+fn draw_line_thick() {
+    let color = [0, 255, 20, 5];
+    // A width of 1 is the same as `draw_line`.
+    let mut thin = OwnedRgbBuffer::<X, Y>::new();
+    thin.draw_line(3, 2, 30, 12, color);
+    let mut thick = OwnedRgbBuffer::<X, Y>::new();
+    thick.draw_line_thick(3, 2, 30, 12, 1, color);
+    assert_eq!(thick, thin);
+    // A width of 1 is the same as `draw_line` when the line is partially out of bounds.
+    thin.fill([0; 4]);
+    thin.draw_line(3, 2, X + 17, Y + 5, color);
+    thick.fill([0; 4]);
+    thick.draw_line_thick(3, 2, X + 17, Y + 5, 1, color);
+    assert_eq!(thick, thin);
+    // A horizontal line.
+    thick.fill([0; 4]);
+    thick.draw_line_thick(5, 10, 20, 10, 5, color);
+    assert_eq!(thick.bounding_box(color), Some(Rect::new(3, 8, 20, 5)));
+    assert_eq!(thick.pixels()[8][12], color);
+    assert_eq!(thick.pixels()[12][12], color);
+    // A line that is partially out of bounds.
+    thick.fill([0; 4]);
+    thick.draw_line_thick(0, 0, 0, Y - 1, 3, color);
+    assert_eq!(thick.bounding_box(color), Some(Rect::new(0, 0, 2, Y)));
+    // A width of 0 doesn't draw anything.
+    thick.fill([0; 4]);
+    thick.draw_line_thick(0, 0, 10, 10, 0, color);
+    assert_eq!(thick.count_color(color), 0);
+    // A huge width doesn't overflow.
+    thick.draw_line_thick(0, 0, 10, 10, 10_000_000_000, color);
+    assert_eq!(thick.count_color(color), X * Y);
+}
+
+#[test]
+fn fill_circle_huge() {
+    // Huge radii don't overflow.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_circle(10, 10, 5_000_000_000, RED);
+    assert_eq!(rgb_buffer.count_color(RED), X * Y);
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_circle(10, 10, usize::MAX, RED);
+    assert_eq!(rgb_buffer.count_color(RED), X * Y);
+    // Only the edge of a huge circle whose center is far out of bounds is visible.
+    let r = 1_000_000_000;
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_circle(X + r, 10, r + 5, RED);
+    assert_eq!(rgb_buffer.pixels()[10][X - 5], RED);
+    assert_eq!(rgb_buffer.pixels()[10][X - 6], BLACK);
+    // The ring's hole is the same as the circle above.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_ring(X + r, 10, r + 5, usize::MAX, RED);
+    assert_eq!(rgb_buffer.pixels()[10][X - 6], RED);
+    assert_eq!(rgb_buffer.pixels()[10][X - 5], BLACK);
+    // Draw contexts fill circles the same way.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer
+        .draw_context(BlendMode::Replace)
+        .fill_circle(10, 10, usize::MAX, RED);
+    assert_eq!(rgb_buffer.count_color(RED), X * Y);
+}
+
+#[test]
+fn line_points() {
+    let points = softbuffer_rgb::line_points(0, 0, 4, 2).collect::<Vec<(isize, isize)>>();
+    assert_eq!(points, [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+    // Lines can go backwards and out of bounds.
+    let points = softbuffer_rgb::line_points(1, 1, -1, -1).collect::<Vec<(isize, isize)>>();
+    assert_eq!(points, [(1, 1), (0, 0), (-1, -1)]);
+    assert_eq!(softbuffer_rgb::line_points(3, 3, 3, 3).count(), 1);
+    // The points are the pixels that `draw_line` draws.
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_line(3, 2, 30, 12, color);
+    let mut points = OwnedRgbBuffer::<X, Y>::new();
+    for (x, y) in softbuffer_rgb::line_points(3, 2, 30, 12) {
+        points.pixels_mut()[y as usize][x as usize] = color;
+    }
+    assert_eq!(points, rgb_buffer);
+}
+
+#[test]
+fn draw_line_clipped() {
+    // Clipping doesn't change the slope of a line that leaves the buffer.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_line(0, 0, 100, 3, RED);
+    assert_eq!(rgb_buffer.pixels()[0][16], RED);
+    assert_eq!(rgb_buffer.pixels()[1][17], RED);
+    // Clipped lines draw the points of the unclipped line that are in bounds.
+    let mut points = OwnedRgbBuffer::<X, Y>::new();
+    let coords = (0..100).step_by(13);
+    for x0 in coords.clone() {
+        for y0 in coords.clone() {
+            for x1 in coords.clone() {
+                for y1 in coords.clone() {
+                    rgb_buffer.fill(BLACK);
+                    rgb_buffer.draw_line(x0, y0, x1, y1, RED);
+                    points.fill(BLACK);
+                    softbuffer_rgb::line_points(x0 as isize, y0 as isize, x1 as isize, y1 as isize)
+                        .filter(|(x, y)| (*x as usize) < X && (*y as usize) < Y)
+                        .for_each(|(x, y)| points.pixels_mut()[y as usize][x as usize] = RED);
+                    assert_eq!(points, rgb_buffer, "({x0}, {y0}) to ({x1}, {y1})");
+                }
+            }
+        }
+    }
+    // Lines with huge endpoints are clipped without stepping through every point.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_line(0, 5, usize::MAX / 4, 5, RED);
+    assert_eq!(rgb_buffer.count_color(RED), X);
+}
+
+#[test]
+fn draw_line_dashed() {
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_line_dashed(0, 5, 9, 5, color, 3, 2, 0);
+    let xs = (0..X)
+        .filter(|x| rgb_buffer.pixels()[5][*x] == color)
+        .collect::<Vec<usize>>();
+    assert_eq!(xs, [0, 1, 2, 5, 6, 7]);
+    // Advance the phase.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line_dashed(0, 5, 9, 5, color, 3, 2, 6);
+    let xs = (0..X)
+        .filter(|x| rgb_buffer.pixels()[5][*x] == color)
+        .collect::<Vec<usize>>();
+    assert_eq!(xs, [0, 1, 4, 5, 6, 9]);
+    // Without gaps, this is the same as `draw_line`.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line_dashed(3, 2, 30, 12, color, 4, 0, 0);
+    let mut line = OwnedRgbBuffer::<X, Y>::new();
+    line.draw_line(3, 2, 30, 12, color);
+    assert_eq!(rgb_buffer, line);
+    // Out of bounds pixels aren't drawn.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line_dashed(X - 2, 0, X + 3, 0, color, 3, 2, 0);
+    assert_eq!(rgb_buffer.count_color(color), 2);
+    // Points that are clipped before the buffer still advance the dash pattern.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line_dashed(X + 7, 5, 0, 5, color, 3, 2, 1);
+    let xs = (0..X)
+        .filter(|x| rgb_buffer.pixels()[5][*x] == color)
+        .collect::<Vec<usize>>();
+    let expected = (0..X)
+        .filter(|x| (X + 7 - x + 1) % 5 < 3)
+        .collect::<Vec<usize>>();
+    assert_eq!(xs, expected);
+    // Lines with huge endpoints are clipped without stepping through every point.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line_dashed(0, 5, usize::MAX / 4, 5, color, 3, 2, 0);
+    assert_eq!(rgb_buffer.count_color(color), X / 5 * 3);
+    // A dash of 0 doesn't draw anything.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_line_dashed(0, 0, 10, 10, color, 0, 2, 0);
+    assert_eq!(rgb_buffer.count_color(color), 0);
+}
+
+#[test]
+fn fill_polygon() {
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // A rectangle.
+    rgb_buffer.fill_polygon(&[(2, 3), (6, 3), (6, 8), (2, 8)], color);
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.fill_rect(Rect::new(2, 3, 4, 5), color);
+    assert_eq!(rgb_buffer, expected);
+    // A concave U shape with a collinear vertex.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_polygon(
+        &[
+            (0, 0),
+            (5, 0),
+            (9, 0),
+            (9, 9),
+            (6, 9),
+            (6, 3),
+            (3, 3),
+            (3, 9),
+            (0, 9),
+        ],
+        color,
+    );
+    assert_eq!(rgb_buffer.count_color(color), 9 * 9 - 3 * 6);
+    assert_eq!(rgb_buffer.pixels()[5][4], [0; 4]);
+    // A self-intersecting bowtie.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_polygon(&[(0, 0), (10, 10), (10, 0), (0, 10)], color);
+    assert_eq!(rgb_buffer.pixels()[1][5], [0; 4]);
+    assert_eq!(rgb_buffer.pixels()[5][1], color);
+    assert_eq!(rgb_buffer.pixels()[5][8], color);
+    // A triangle that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_polygon(&[(-10, -10), (100, 20), (20, 100)], color);
+    assert_eq!(rgb_buffer.pixels()[0][0], color);
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], color);
+    // Edges between extreme coordinates don't overflow.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_polygon(&[(i32::MIN, -10), (i32::MAX, 40), (i32::MIN, 40)], color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(0, 15, X, Y - 15))
+    );
+    assert_eq!(rgb_buffer.count_color(color), X * (Y - 15));
+    // Too few points.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_polygon(&[(0, 0), (10, 10)], color);
+    assert_eq!(rgb_buffer.count_color(color), 0);
+}
+
+#[test]
+fn colors() {
+    assert_eq!(rgb(200, 100, 70), [0, 200, 100, 70]);
+    assert_eq!(WHITE, [0, 255, 255, 255]);
+    assert_eq!(from_u32(to_u32(RED)), RED);
+    assert_eq!(to_u32(BLUE), u32::from_le_bytes(BLUE));
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y);
+    rgb_buffer.fill(rgb(200, 100, 70));
+    assert_eq!(rgb_buffer.to_vec()[0], to_u32([0, 200, 100, 70]));
+}
+
+#[test]
+fn draw_bezier_quadratic() {
+    let color = [0, 255, 20, 5];
+    // A curve whose control point is on the line is a line.
+    let mut line = OwnedRgbBuffer::<X, Y>::new();
+    line.draw_line(2, 3, 30, 3, color);
+    let mut curve = OwnedRgbBuffer::<X, Y>::new();
+    curve.draw_bezier_quadratic((2.0, 3.0), (16.0, 3.0), (30.0, 3.0), color);
+    assert_eq!(curve, line);
+    // The curve reaches halfway to the control point.
+    curve.fill([0; 4]);
+    curve.draw_bezier_quadratic((2.0, 2.0), (20.0, 26.0), (38.0, 2.0), color);
+    assert_eq!(curve.bounding_box(color), Some(Rect::new(2, 2, 37, 13)));
+    assert_eq!(curve.pixels()[14][20], color);
+    // The curve is continuous: every row between the ends and the peak is drawn.
+    assert!((2..=14).all(|y| curve.pixels()[y].contains(&color)));
+    // A curve that is partially out of bounds.
+    curve.fill([0; 4]);
+    curve.draw_bezier_quadratic((-10.0, 20.0), (20.0, -40.0), (50.0, 20.0), color);
+    assert_eq!(curve.pixels()[0][20], [0; 4]);
+    assert!(curve.count_color(color) > 0);
+}
+
+#[test]
+fn histogram() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_rect(Rect::new(0, 0, 4, 5), rgb(255, 20, 0));
+    let bins = rgb_buffer.histogram();
+    assert_eq!(bins[0][255], 20);
+    assert_eq!(bins[0][0], (X * Y - 20) as u32);
+    assert_eq!(bins[1][20], 20);
+    assert_eq!(bins[2][0], (X * Y) as u32);
+    assert!(bins
+        .iter()
+        .all(|channel| channel.iter().sum::<u32>() == (X * Y) as u32));
+}
+
+#[test]
+fn stretch_contrast() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(rgb(50, 0, 7));
+    rgb_buffer.fill_rect(Rect::new(0, 0, 4, 5), rgb(100, 255, 7));
+    rgb_buffer.pixels_mut()[Y - 1][X - 1] = rgb(75, 128, 7);
+    rgb_buffer.stretch_contrast();
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(255, 255, 7));
+    assert_eq!(rgb_buffer.pixels()[10][10], rgb(0, 0, 7));
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], rgb(128, 128, 7));
+    // Stretching again doesn't change anything.
+    let stretched = rgb_buffer.clone();
+    rgb_buffer.stretch_contrast();
+    assert_eq!(rgb_buffer, stretched);
+}
+
+#[test]
+fn shade() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // An XOR pattern.
+    rgb_buffer.shade(|x, y| rgb((x ^ y) as u8, x as u8, y as u8));
+    assert_eq!(rgb_buffer.pixels()[5][3], rgb(6, 3, 5));
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], rgb(58, 39, 29));
+    // The closure is called in row-major order.
+    let mut i = 0;
+    rgb_buffer.shade(|x, y| {
+        assert_eq!(i, y * X + x);
+        i += 1;
+        BLACK
+    });
+    assert_eq!(i, X * Y);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_shade() {
+    let f = |x: usize, y: usize| rgb((x ^ y) as u8, x as u8, y as u8);
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(f);
+    let mut par = OwnedRgbBuffer::<X, Y>::new();
+    par.par_shade(f);
+    assert_eq!(par, rgb_buffer);
+}
+
+#[test]
+fn copy_rows() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|_, y| rgb(y as u8, 0, 0));
+    // Move rows down by 2. The source and destination overlap.
+    rgb_buffer.copy_rows(0, 2, 10).unwrap();
+    assert_eq!(rgb_buffer.pixels()[1][0], rgb(1, 0, 0));
+    assert_eq!(rgb_buffer.pixels()[2][0], rgb(0, 0, 0));
+    assert_eq!(rgb_buffer.pixels()[11][X - 1], rgb(9, 0, 0));
+    assert_eq!(rgb_buffer.pixels()[12][0], rgb(12, 0, 0));
+    // Move rows up.
+    rgb_buffer.copy_rows(Y - 5, 0, 5).unwrap();
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(Y as u8 - 5, 0, 0));
+    assert_eq!(rgb_buffer.pixels()[4][0], rgb(Y as u8 - 1, 0, 0));
+    // Out of bounds.
+    assert!(matches!(
+        rgb_buffer.copy_rows(0, Y - 4, 5),
+        Err(RgbBufferError::InvalidPosition { x: 0, y }) if y == Y - 4
+    ));
+    assert!(matches!(
+        rgb_buffer.copy_rows(usize::MAX, 0, 1),
+        Err(RgbBufferError::InvalidPosition {
+            x: 0,
+            y: usize::MAX
+        })
+    ));
+}
+
+#[test]
+fn clip_line() {
+    let rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // Inside.
+    assert_eq!(rgb_buffer.clip_line(1, 2, 3, 4), Some((1, 2, 3, 4)));
+    // Partially outside.
+    assert_eq!(
+        rgb_buffer.clip_line(0, 5, X + 10, 5),
+        Some((0, 5, X - 1, 5))
+    );
+    assert_eq!(
+        rgb_buffer.clip_line(Y + 9, Y + 9, 0, 0),
+        Some((Y - 1, Y - 1, 0, 0))
+    );
+    // Outside.
+    assert_eq!(rgb_buffer.clip_line(X, 0, X + 10, Y + 10), None);
+    assert_eq!(rgb_buffer.clip_line(X + 5, Y - 5, X - 5, Y + 5), None);
+    // A long line that is mostly out of bounds.
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_line(0, 10, usize::MAX / 4, 10, color);
+    assert_eq!(rgb_buffer.count_color(color), X);
+    rgb_buffer.draw_line_thick(X + 1, 0, X + 1, Y - 1, 5, color);
+    assert_eq!(rgb_buffer.count_color(color), X + Y - 1);
+}
+
+#[test]
+fn fill_rounded_rectangle() {
+    let color = [0, 255, 20, 5];
+    // A radius of 0 is a rectangle.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_rounded_rectangle(2, 3, 10, 8, 0, color);
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.fill_rect(Rect::new(2, 3, 10, 8), color);
+    assert_eq!(rgb_buffer, expected);
+    // A square with the largest radius is a circle.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_rounded_rectangle(5, 5, 11, 11, 100, color);
+    expected.fill([0; 4]);
+    expected.fill_circle(10, 10, 5, color);
+    assert_eq!(rgb_buffer, expected);
+    // Rounded corners.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_rounded_rectangle(2, 3, 20, 10, 3, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(2, 3, 20, 10))
+    );
+    for (x, y) in [(2, 3), (21, 3), (2, 12), (21, 12)] {
+        assert_eq!(rgb_buffer.pixels()[y][x], [0; 4]);
+    }
+    assert_eq!(rgb_buffer.pixels()[3][5], color);
+    assert_eq!(rgb_buffer.pixels()[6][2], color);
+    assert_eq!(rgb_buffer.pixels()[12][18], color);
+    // A rectangle that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_rounded_rectangle(X - 5, Y - 5, 20, 20, 3, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(X - 5, Y - 5, 5, 5))
+    );
+    assert_eq!(rgb_buffer.pixels()[Y - 5][X - 5], [0; 4]);
+}
+
+#[test]
+fn connected_bounds() {
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // An L shape and a separate square.
+    rgb_buffer.fill_rect(Rect::new(2, 3, 2, 10), color);
+    rgb_buffer.fill_rect(Rect::new(2, 11, 8, 2), color);
+    rgb_buffer.fill_rect(Rect::new(20, 20, 3, 3), color);
+    // A diagonal neighbor isn't connected.
+    rgb_buffer.pixels_mut()[2][1] = color;
+    assert_eq!(
+        rgb_buffer.connected_bounds(9, 12),
+        Some(Rect::new(2, 3, 8, 10))
+    );
+    assert_eq!(
+        rgb_buffer.connected_bounds(21, 21),
+        Some(Rect::new(20, 20, 3, 3))
+    );
+    assert_eq!(
+        rgb_buffer.connected_bounds(1, 2),
+        Some(Rect::new(1, 2, 1, 1))
+    );
+    // The background surrounds everything.
+    assert_eq!(
+        rgb_buffer.connected_bounds(0, 0),
+        Some(Rect::new(0, 0, X, Y))
+    );
+    assert_eq!(rgb_buffer.connected_bounds(X, 0), None);
+}
+
+#[test]
+fn set_pixels_with_colors() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let pixels = [((1, 2), RED), ((X - 1, Y - 1), GREEN), ((0, 0), BLUE)];
+    rgb_buffer.set_pixels_with_colors(&pixels);
+    assert_eq!(rgb_buffer.pixels()[2][1], RED);
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], GREEN);
+    assert_eq!(rgb_buffer.pixels()[0][0], BLUE);
+    // Nothing is set if any position is out of bounds.
+    rgb_buffer.fill(BLACK);
+    assert!(matches!(
+        rgb_buffer.try_set_pixels_with_colors(&[((1, 2), RED), ((X, 0), RED)]),
+        Err(RgbBufferError::InvalidPosition { x, y: 0 }) if x == X
+    ));
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y);
+    rgb_buffer.try_set_pixels_with_colors(&pixels).unwrap();
+    assert_eq!(rgb_buffer.pixels()[2][1], RED);
+}
+
+#[test]
+fn downsample_2x_into() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| if (x + y) % 2 == 0 { WHITE } else { BLACK });
+    rgb_buffer.fill_rect(Rect::new(4, 6, 2, 2), RED);
+    let mut half = OwnedRgbBuffer::<{ X / 2 }, { Y / 2 }>::new();
+    // Every byte of the destination is replaced, including the leading zero.
+    half.fill([255; 4]);
+    rgb_buffer.downsample_2x_into(&mut half).unwrap();
+    assert_eq!(half.pixels()[0][0], rgb(128, 128, 128));
+    assert_eq!(half.pixels()[3][2], RED);
+    assert_eq!(half.count_color(rgb(128, 128, 128)), X * Y / 4 - 1);
+    let mut wrong = OwnedRgbBuffer::<{ X / 2 }, Y>::new();
+    assert!(matches!(
+        rgb_buffer.downsample_2x_into(&mut wrong),
+        Err(RgbBufferError::InvalidSize { x: 20, y: 30 })
+    ));
+}
+
+#[test]
+fn downscale_into() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| if (x + y) % 2 == 0 { WHITE } else { BLACK });
+    // A factor of 2 is the same as `downsample_2x_into`.
+    let mut half = OwnedRgbBuffer::<{ X / 2 }, { Y / 2 }>::new();
+    rgb_buffer.downscale_into(2, &mut half).unwrap();
+    let mut expected = OwnedRgbBuffer::<{ X / 2 }, { Y / 2 }>::new();
+    rgb_buffer.downsample_2x_into(&mut expected).unwrap();
+    assert_eq!(half, expected);
+    // A 3x3 block of the checkerboard has 5 white pixels, or 4 if it starts on a black pixel.
+    rgb_buffer.fill_rect(Rect::new(3, 6, 3, 3), RED);
+    let mut third = OwnedRgbBuffer::<{ X / 3 }, { Y / 3 }>::new();
+    rgb_buffer.downscale_into(3, &mut third).unwrap();
+    assert_eq!(third.pixels()[0][0], rgb(142, 142, 142));
+    assert_eq!(third.pixels()[0][1], rgb(113, 113, 113));
+    assert_eq!(third.pixels()[2][1], RED);
+    assert!(matches!(
+        rgb_buffer.downscale_into(2, &mut third),
+        Err(RgbBufferError::InvalidSize { x: 13, y: 10 })
+    ));
+    assert!(matches!(
+        rgb_buffer.downscale_into(0, &mut third),
+        Err(RgbBufferError::InvalidSize { x: 13, y: 10 })
+    ));
+}
+
+#[test]
+fn swap_red_blue() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(rgb(1, 2, 3));
+    rgb_buffer.swap_red_blue();
+    assert_eq!(rgb_buffer.count_color(rgb(3, 2, 1)), X * Y);
+    // Copy a 2x2 BGR image that is partially out of bounds.
+    let bgr = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+    rgb_buffer.blit_bgr(X - 1, 5, 2, &bgr).unwrap();
+    assert_eq!(rgb_buffer.pixels()[5][X - 1], rgb(3, 2, 1));
+    assert_eq!(rgb_buffer.pixels()[6][X - 1], rgb(9, 8, 7));
+    assert_eq!(rgb_buffer.pixels()[6][0], rgb(3, 2, 1));
+    assert!(matches!(
+        rgb_buffer.blit_bgr(0, 0, 3, &bgr),
+        Err(RgbBufferError::InvalidSize { x: 3, y: 12 })
+    ));
+    assert!(rgb_buffer.blit_bgr(0, 0, 0, &bgr).is_err());
+}
+
+#[test]
+fn draw_grid() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // Lines that are partially out of bounds.
+    rgb_buffer.draw_hline(X - 3, 4, 10, RED);
+    rgb_buffer.draw_vline(4, Y - 3, 10, RED);
+    assert_eq!(rgb_buffer.count_color(RED), 6);
+    rgb_buffer.draw_hline(0, Y, 10, RED);
+    rgb_buffer.draw_vline(X, 0, 10, RED);
+    assert_eq!(rgb_buffer.count_color(RED), 6);
+    // A grid with 4 vertical lines and 3 horizontal lines.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_grid(10, WHITE).unwrap();
+    assert_eq!(rgb_buffer.count_color(WHITE), 4 * Y + 3 * X - 12);
+    assert_eq!(rgb_buffer.pixels()[10][0], WHITE);
+    assert_eq!(rgb_buffer.pixels()[11][30], WHITE);
+    assert_eq!(rgb_buffer.pixels()[11][11], BLACK);
+    // Scroll the grid.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_grid_offset(10, 3, 25, WHITE).unwrap();
+    assert_eq!(rgb_buffer.pixels()[1][7], WHITE);
+    assert_eq!(rgb_buffer.pixels()[5][1], WHITE);
+    assert_eq!(rgb_buffer.pixels()[1][0], BLACK);
+    assert!(matches!(
+        rgb_buffer.draw_grid(0, WHITE),
+        Err(RgbBufferError::InvalidSize { x: 0, y: 0 })
+    ));
+}
+
+#[test]
+fn sample_nearest() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| rgb(x as u8, y as u8, 0));
+    assert_eq!(rgb_buffer.get_clamped(3, 4), rgb(3, 4, 0));
+    assert_eq!(rgb_buffer.get_clamped(-3, 4), rgb(0, 4, 0));
+    assert_eq!(
+        rgb_buffer.get_clamped(isize::MAX, isize::MIN),
+        rgb(X as u8 - 1, 0, 0)
+    );
+    assert_eq!(rgb_buffer.sample_nearest(3.4, 4.6), rgb(3, 5, 0));
+    assert_eq!(
+        rgb_buffer.sample_nearest(-10.0, 1000.0),
+        rgb(0, Y as u8 - 1, 0)
+    );
+    assert_eq!(rgb_buffer.sample_nearest(f32::NAN, 2.0), rgb(0, 2, 0));
+}
+
+#[test]
+fn rotate_180() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| rgb(x as u8, y as u8, 0));
+    let original = rgb_buffer.clone();
+    rgb_buffer.rotate_180();
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(X as u8 - 1, Y as u8 - 1, 0));
+    assert_eq!(rgb_buffer.pixels()[Y - 1 - 4][X - 1 - 3], rgb(3, 4, 0));
+    rgb_buffer.rotate_180();
+    assert_eq!(rgb_buffer, original);
+}
+
+#[test]
+fn fill_triangle_gradient() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // A triangle with one color.
+    rgb_buffer.fill_triangle_gradient((2, 2, RED), (12, 2, RED), (2, 12, RED));
+    assert_eq!(rgb_buffer.bounding_box(RED), Some(Rect::new(2, 2, 10, 10)));
+    assert_eq!(rgb_buffer.count_color(RED), 55);
+    // The winding order doesn't matter.
+    let mut reversed = OwnedRgbBuffer::<X, Y>::new();
+    reversed.fill_triangle_gradient((2, 12, RED), (12, 2, RED), (2, 2, RED));
+    assert_eq!(reversed, rgb_buffer);
+    // Interpolate the colors of a triangle that is partially out of bounds.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_triangle_gradient((0, 0, RED), (60, 0, GREEN), (0, 60, BLUE));
+    let corner = rgb_buffer.pixels()[0][0];
+    assert!(corner[1] > 250 && corner[2] < 5 && corner[3] < 5);
+    assert_eq!(rgb_buffer.pixels()[0][X - 1], rgb(85, 168, 2));
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], BLACK);
+    assert!(rgb_buffer.pixels().iter().flatten().all(|c| c[0] == 0));
+    // Collinear vertices don't draw anything.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_triangle_gradient((0, 0, RED), (5, 5, RED), (10, 10, RED));
+    assert_eq!(rgb_buffer.count_color(RED), 0);
+}
+
+#[test]
+fn rgba() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.pixels_mut()[0][1] = rgb(1, 2, 3);
+    let rgba = rgb_buffer.to_rgba_vec();
+    assert_eq!(rgba.len(), X * Y * 4);
+    assert_eq!(rgba[..8], [0, 0, 0, 255, 1, 2, 3, 255]);
+    // Load the pixels back into another buffer.
+    let mut loaded = OwnedRgbBuffer::<X, Y>::new();
+    loaded.fill(WHITE);
+    loaded.load_rgba(X, &rgba).unwrap();
+    assert_eq!(loaded, rgb_buffer);
+    // Load an image that is wider than the buffer. Alpha is ignored.
+    let mut image = vec![0; (X + 2) * 2 * 4];
+    image[(X + 2) * 4..(X + 2) * 4 + 4].copy_from_slice(&[4, 5, 6, 0]);
+    loaded.load_rgba(X + 2, &image).unwrap();
+    assert_eq!(loaded.pixels()[1][0], rgb(4, 5, 6));
+    assert_eq!(loaded.count_color(BLACK), X * Y - 1);
+    assert!(matches!(
+        loaded.load_rgba(X, &rgba[1..]),
+        Err(RgbBufferError::InvalidSize { x: X, y }) if y == X * Y * 4 - 1
+    ));
+}
+
+#[test]
+fn fill_if() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, _| rgb(x as u8 * 6, 0, 0));
+    // Replace everything darker than a threshold.
+    rgb_buffer.fill_if(WHITE, |_, _, c| luminance(c) < 10);
+    assert_eq!(rgb_buffer.count_color(WHITE), 6 * Y);
+    // Fill a custom-shaped region.
+    rgb_buffer.fill_if(BLUE, |x, y, _| x == y);
+    assert_eq!(rgb_buffer.count_color(BLUE), Y);
+    assert_eq!(rgb_buffer.count_color(WHITE), 6 * Y - 6);
+}
+
+#[test]
+fn blit_masked() {
+    // A mask that crosses a 64-bit boundary.
+    let mut mask = Mask::<9, 9>::new();
+    for i in 0..9 {
+        mask.set(i, i, true);
+        mask.set(8 - i, i, true);
+    }
+    mask.set(4, 4, false);
+    assert!(mask.get(0, 0) && mask.get(8, 8) && mask.get(1, 7));
+    assert!(!mask.get(4, 4) && !mask.get(1, 0) && !mask.get(9, 0));
+    // Copy an X-shaped sprite that is partially out of bounds.
+    let sprite = [RED; 81];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.blit_masked(X - 5, 0, 9, &sprite, &mask).unwrap();
+    assert_eq!(rgb_buffer.count_color(RED), 8);
+    assert_eq!(rgb_buffer.pixels()[0][X - 5], RED);
+    assert_eq!(rgb_buffer.pixels()[4][X - 1], BLACK);
+    assert_eq!(rgb_buffer.pixels()[3][X - 2], RED);
+    assert!(matches!(
+        rgb_buffer.blit_masked(0, 0, 9, &sprite[1..], &mask),
+        Err(RgbBufferError::InvalidSize { x: 9, y: 80 })
+    ));
+}
+
+#[test]
+fn draw_text_scaled() {
+    let mut text = OwnedRgbBuffer::<X, Y>::new();
+    text.draw_text(1, 2, "Hi", WHITE);
+    // A scale of 1 is the same as `draw_text`.
+    let mut scaled = OwnedRgbBuffer::<X, Y>::new();
+    scaled.draw_text_scaled(1, 2, "Hi", WHITE, 1);
+    assert_eq!(scaled, text);
+    // Each pixel becomes a 2x2 block.
+    scaled.fill(BLACK);
+    scaled.draw_text_scaled(0, 0, "H", WHITE, 2);
+    let mut unscaled = OwnedRgbBuffer::<X, Y>::new();
+    unscaled.draw_text(0, 0, "H", WHITE);
+    assert_eq!(scaled.count_color(WHITE), 4 * unscaled.count_color(WHITE));
+    for y in 0..16 {
+        for x in 0..16 {
+            assert_eq!(scaled.pixels()[y][x], unscaled.pixels()[y / 2][x / 2]);
+        }
+    }
+    // Text that is partially out of bounds.
+    scaled.fill(BLACK);
+    scaled.draw_text_scaled(X - 10, Y - 10, "H", WHITE, 3);
+    assert_eq!(
+        scaled.bounding_box(WHITE),
+        Some(Rect::new(X - 10, Y - 10, 10, 10))
+    );
+}
+
+#[test]
+fn blit_over_checker() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // A transparent sprite shows the checkerboard.
+    let sprite = [RED; 16];
+    let mut alpha = [0; 16];
+    rgb_buffer
+        .blit_over_checker(2, 3, 4, &sprite, &alpha, 2)
+        .unwrap();
+    assert_eq!(rgb_buffer.pixels()[3][2], WHITE);
+    assert_eq!(rgb_buffer.pixels()[4][3], WHITE);
+    assert_eq!(rgb_buffer.pixels()[3][4], rgb(204, 204, 204));
+    assert_eq!(rgb_buffer.pixels()[6][5], WHITE);
+    assert_eq!(rgb_buffer.count_color(WHITE), 8);
+    // Opaque pixels cover the checkerboard, and translucent pixels are blended.
+    alpha[0] = 255;
+    alpha[1] = 128;
+    rgb_buffer
+        .blit_over_checker(X - 2, 0, 4, &sprite, &alpha, 2)
+        .unwrap();
+    assert_eq!(rgb_buffer.pixels()[0][X - 2], RED);
+    assert_eq!(rgb_buffer.pixels()[0][X - 1], rgb(255, 127, 127));
+    // Errors.
+    assert!(matches!(
+        rgb_buffer.blit_over_checker(0, 0, 4, &sprite, &alpha[1..], 2),
+        Err(RgbBufferError::InvalidSize { x: 4, y: 15 })
+    ));
+    assert!(matches!(
+        rgb_buffer.blit_over_checker(0, 0, 3, &sprite, &alpha, 2),
+        Err(RgbBufferError::InvalidSize { x: 3, y: 16 })
+    ));
+    assert!(rgb_buffer
+        .blit_over_checker(0, 0, 4, &sprite, &alpha, 0)
+        .is_err());
+}
+
+#[test]
+fn average_color() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_rect(Rect::new(0, 0, 2, 4), WHITE);
+    rgb_buffer.pixels_mut()[0][2] = RED;
+    assert_eq!(rgb_buffer.luminance_at(0, 0), Some(255));
+    assert_eq!(rgb_buffer.luminance_at(2, 0), Some(luminance(RED)));
+    assert_eq!(rgb_buffer.luminance_at(X, 0), None);
+    assert_eq!(
+        rgb_buffer.average_color(Rect::new(0, 0, 4, 4)),
+        rgb(143, 128, 128)
+    );
+    assert_eq!(rgb_buffer.average_color(Rect::new(0, 0, 1, 1)), WHITE);
+    // The rectangle is clipped.
+    assert_eq!(
+        rgb_buffer.average_color(Rect::new(X - 2, 0, 100, 100)),
+        BLACK
+    );
+    assert_eq!(rgb_buffer.average_color(Rect::new(X, 0, 1, 1)), BLACK);
+}
+
+#[test]
+fn typed_rgb() {
+    let color = Rgb(200, 100, 70);
+    assert_eq!((color.r(), color.g(), color.b()), (200, 100, 70));
+    assert_eq!(color.to_color(), rgb(200, 100, 70));
+    assert_eq!(Color::from(color), rgb(200, 100, 70));
+    assert_eq!(Rgb::from_color([9, 200, 100, 70]), color);
+    assert_eq!(Rgb::from(rgb(200, 100, 70)), color);
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_rgb(color);
+    assert_eq!(rgb_buffer.count_color(rgb(200, 100, 70)), X * Y);
+}
+
+#[test]
+fn blit_rotated() {
+    // A 4x2 sprite with a transparent pixel.
+    let key = MAGENTA;
+    let sprite = [RED, GREEN, BLUE, WHITE, key, RED, GREEN, BLUE];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // An angle of 0 copies the sprite.
+    rgb_buffer
+        .blit_rotated(10, 10, 4, &sprite, 0.0, key)
+        .unwrap();
+    assert_eq!(rgb_buffer.pixels()[9][8..12], [RED, GREEN, BLUE, WHITE]);
+    assert_eq!(rgb_buffer.pixels()[10][8..12], [BLACK, RED, GREEN, BLUE]);
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y - 7);
+    // Rotate by 90 degrees clockwise.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer
+        .blit_rotated(10, 10, 4, &sprite, std::f32::consts::FRAC_PI_2, key)
+        .unwrap();
+    assert_eq!(rgb_buffer.bounding_box(RED), Some(Rect::new(9, 8, 2, 2)));
+    assert_eq!(rgb_buffer.pixels()[8][10], RED);
+    assert_eq!(rgb_buffer.pixels()[11][10], WHITE);
+    assert_eq!(rgb_buffer.pixels()[8][9], BLACK);
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y - 7);
+    // Rotating by 180 degrees is the same as `rotate_180` on the copied sprite.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer
+        .blit_rotated(X / 2, Y / 2, 4, &sprite, std::f32::consts::PI, key)
+        .unwrap();
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected
+        .blit_rotated(X / 2, Y / 2, 4, &sprite, 0.0, key)
+        .unwrap();
+    expected.rotate_180();
+    assert_eq!(rgb_buffer, expected);
+    // A sprite that is partially out of bounds.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.blit_rotated(0, 0, 4, &sprite, 1.0, key).unwrap();
+    assert!(rgb_buffer.count_color(BLACK) < X * Y);
+    assert!(rgb_buffer.blit_rotated(0, 0, 3, &sprite, 0.0, key).is_err());
+}
+
+#[test]
+fn try_fill_rect() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer
+        .try_fill_rectangle(X - 2, Y - 2, 2, 2, RED)
+        .unwrap();
+    assert_eq!(rgb_buffer.count_color(RED), 4);
+    // The rectangle is too big.
+    assert!(matches!(
+        rgb_buffer.try_fill_rectangle(X - 2, Y - 2, 3, 2, RED),
+        Err(RgbBufferError::InvalidSize { x: 3, y: 2 })
+    ));
+    assert!(matches!(
+        rgb_buffer.try_fill_rect(Rect::new(1, 1, usize::MAX, usize::MAX), RED),
+        Err(RgbBufferError::InvalidSize { .. })
+    ));
+    // The position is out of bounds.
+    assert!(matches!(
+        rgb_buffer.try_fill_rect(Rect::new(usize::MAX, 0, 1, 1), RED),
+        Err(RgbBufferError::InvalidPosition {
+            x: usize::MAX,
+            y: 0
+        })
+    ));
+    assert_eq!(rgb_buffer.count_color(RED), 4);
+}
+
+#[test]
+#[should_panic(expected = "Invalid size")]
+fn fill_rect_overflow() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_rect(Rect::new(1, 1, usize::MAX, 1), RED);
+}
+
+#[test]
+fn color_format() {
+    assert_eq!(ColorFormat::default(), ColorFormat::Xrgb);
+    assert_eq!(ColorFormat::Xrgb.rgb(1, 2, 3), rgb(1, 2, 3));
+    assert_eq!(ColorFormat::Rgbx.rgb(1, 2, 3), [1, 2, 3, 0]);
+    assert_eq!(ColorFormat::Xbgr.rgb(1, 2, 3), [0, 3, 2, 1]);
+    assert_eq!(ColorFormat::Bgrx.rgb(1, 2, 3), [3, 2, 1, 0]);
+    for format in [
+        ColorFormat::Xrgb,
+        ColorFormat::Rgbx,
+        ColorFormat::Xbgr,
+        ColorFormat::Bgrx,
+    ] {
+        assert_eq!(format.to_color(format.rgb(1, 2, 3)), rgb(1, 2, 3));
+        assert_eq!(format.from_color(rgb(1, 2, 3)), format.rgb(1, 2, 3));
+    }
+    // Convert an `[r, g, b, a]` color.
+    assert_eq!(ColorFormat::Rgbx.to_color([1, 2, 3, 255]), rgb(1, 2, 3));
+}
+
+#[test]
+fn debug_ascii() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // Fill the right half of the buffer.
+    rgb_buffer.fill_rectangle(X / 2, 0, X / 2, Y, WHITE);
+    assert_eq!(rgb_buffer.debug_ascii(4, 2), "  @@\n  @@");
+    // A gray cell.
+    rgb_buffer.fill_rectangle(0, 0, X / 4, Y / 2, GRAY);
+    assert_eq!(rgb_buffer.debug_ascii(4, 2), "+ @@\n  @@");
+    // More cells than pixels.
+    assert_eq!(rgb_buffer.debug_ascii(X * 2, 1).len(), X * 2);
+    assert_eq!(rgb_buffer.debug_ascii(0, 2), "");
+}
+
+#[test]
+fn draw_ellipse_huge() {
+    // Huge radii don't overflow.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_ellipse(8, 8, 4_000_000, 4_000_000, RED);
+    assert_eq!(rgb_buffer.count_color(RED), 0);
+    rgb_buffer.draw_ellipse(8, 8, usize::MAX, 3, RED);
+    assert_eq!(rgb_buffer.bounding_box(RED), Some(Rect::new(0, 5, X, 7)));
+    assert_eq!(rgb_buffer.count_color(RED), 2 * X);
+    // Only the top of a huge ellipse whose center is far out of bounds is visible.
+    let r = 1_000_000_000;
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_ellipse(X / 2, r + 5, r, r, RED);
+    assert_eq!(rgb_buffer.bounding_box(RED), Some(Rect::new(0, 5, X, 1)));
+}
+
+#[test]
+fn fill_ellipse() {
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_ellipse(20, 15, 6, 3, color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(14, 12, 13, 7))
+    );
+    assert_eq!(rgb_buffer.pixels()[15][14], color);
+    assert_eq!(rgb_buffer.pixels()[12][14], [0; 4]);
+    // The outline is inside the filled ellipse.
+    let mut outline = OwnedRgbBuffer::<X, Y>::new();
+    outline.draw_ellipse(20, 15, 6, 3, color);
+    for (filled, outline) in rgb_buffer
+        .pixels()
+        .iter()
+        .flatten()
+        .zip(outline.pixels().iter().flatten())
+    {
+        assert!(*outline != color || *filled == color);
+    }
+    // An ellipse that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ellipse(0, 0, 3, 3, color);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(0, 0, 4, 4)));
+    // Degenerate ellipses.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ellipse(10, 10, 4, 0, color);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(6, 10, 9, 1)));
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ellipse(10, 10, 0, 4, color);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(10, 6, 1, 9)));
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ellipse(10, 10, 0, 0, color);
+    assert_eq!(rgb_buffer.count_color(color), 1);
+    // Huge radii don't overflow.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ellipse(8, 8, 4_000_000, 4_000_000, color);
+    assert_eq!(rgb_buffer.count_color(color), X * Y);
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ellipse(8, 8, usize::MAX, 3, color);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(0, 5, X, 7)));
+    assert_eq!(rgb_buffer.count_color(color), 7 * X);
+}
+
+#[test]
+fn tile_blit() {
+    let tile = [RED, GREEN, BLUE, WHITE, MAGENTA, BLACK];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(GRAY);
+    assert!(matches!(
+        rgb_buffer.tile_blit(Rect::new(0, 0, 4, 4), 4, &tile),
+        Err(RgbBufferError::InvalidSize { x: 4, y: 6 })
+    ));
+    assert!(matches!(
+        rgb_buffer.tile_blit(Rect::new(0, 0, 4, 4), 3, &[]),
+        Err(RgbBufferError::InvalidSize { x: 3, y: 0 })
+    ));
+    // A 3x2 tile in a 7x5 rectangle.
+    rgb_buffer
+        .tile_blit(Rect::new(5, 6, 7, 5), 3, &tile)
+        .unwrap();
+    assert_eq!(rgb_buffer.pixels()[6][5], RED);
+    assert_eq!(rgb_buffer.pixels()[6][8], RED);
+    assert_eq!(rgb_buffer.pixels()[7][9], MAGENTA);
+    assert_eq!(rgb_buffer.pixels()[10][11], RED);
+    assert_eq!(rgb_buffer.count_color(GRAY), X * Y - 35);
+    // A rectangle that is partially out of bounds.
+    rgb_buffer.fill(GRAY);
+    rgb_buffer
+        .tile_blit(Rect::new(X - 2, Y - 1, 10, 10), 3, &tile)
+        .unwrap();
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 2], RED);
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], GREEN);
+    assert_eq!(rgb_buffer.count_color(GRAY), X * Y - 2);
+}
+
+#[test]
+fn invert() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| rgb((x * 6) as u8, (y * 8) as u8, (x ^ y) as u8));
+    let mut expected = rgb_buffer.clone();
+    expected.map_pixels(|c| [0, 255 - c[1], 255 - c[2], 255 - c[3]]);
+    rgb_buffer.invert();
+    assert_eq!(rgb_buffer, expected);
+    rgb_buffer.fill([0, 255, 20, 5]);
+    rgb_buffer.invert();
+    assert_eq!(rgb_buffer.count_color([0, 0, 235, 250]), X * Y);
+}
+
+#[test]
+fn draw_marker() {
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_crosshair(10, 10, 3, color);
+    assert_eq!(rgb_buffer.count_color(color), 13);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(7, 7, 7, 7)));
+    let mut plus = OwnedRgbBuffer::<X, Y>::new();
+    plus.draw_marker(10, 10, MarkerShape::Plus, 3, color);
+    assert_eq!(plus, rgb_buffer);
+    // Every shape is `2 * size + 1` pixels wide.
+    for (shape, count) in [
+        (MarkerShape::Cross, 13),
+        (MarkerShape::Square, 24),
+        (MarkerShape::Diamond, 12),
+        (MarkerShape::Circle, 16),
+    ] {
+        rgb_buffer.fill([0; 4]);
+        rgb_buffer.draw_marker(10, 10, shape, 3, color);
+        assert_eq!(rgb_buffer.count_color(color), count, "{shape:?}");
+        assert_eq!(
+            rgb_buffer.bounding_box(color),
+            Some(Rect::new(7, 7, 7, 7)),
+            "{shape:?}"
+        );
+    }
+    // A size of 0 is a single pixel.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_marker(10, 10, MarkerShape::Square, 0, color);
+    assert_eq!(rgb_buffer.count_color(color), 1);
+    // A marker that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_marker(0, 0, MarkerShape::Square, 2, color);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(0, 0, 3, 3)));
+    assert_eq!(rgb_buffer.count_color(color), 5);
+}
+
+#[test]
+fn apply_lut() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill([0, 10, 20, 30]);
+    // Swap the r and b tables, and invert g.
+    let identity = std::array::from_fn(|v| v as u8);
+    let inverted = std::array::from_fn(|v| 255 - v as u8);
+    rgb_buffer.apply_lut(&[identity, inverted, [7; 256]]);
+    assert_eq!(rgb_buffer.count_color([0, 10, 235, 7]), X * Y);
+    // Posterize.
+    rgb_buffer.fill([0, 0, 100, 200]);
+    rgb_buffer.posterize(3);
+    assert_eq!(rgb_buffer.count_color([0, 0, 128, 255]), X * Y);
+    rgb_buffer.posterize(1);
+    assert_eq!(rgb_buffer.count_color([0, 0, 128, 255]), X * Y);
+    // Adjust the brightness.
+    rgb_buffer.adjust_brightness(-20);
+    assert_eq!(rgb_buffer.count_color([0, 0, 108, 235]), X * Y);
+    rgb_buffer.adjust_brightness(40);
+    assert_eq!(rgb_buffer.count_color([0, 40, 148, 255]), X * Y);
+    // Sepia.
+    rgb_buffer.fill(GRAY);
+    rgb_buffer.sepia();
+    assert_eq!(rgb_buffer.count_color([0, 172, 153, 119]), X * Y);
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.sepia();
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y);
+}
+
+#[test]
+fn bounding_box_nonzero() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    assert_eq!(rgb_buffer.bounding_box_nonzero(), None);
+    rgb_buffer.pixels_mut()[4][20] = [0, 0, 0, 1];
+    rgb_buffer.pixels_mut()[9][3] = RED;
+    rgb_buffer.pixels_mut()[12][10] = GREEN;
+    assert_eq!(
+        rgb_buffer.bounding_box_nonzero(),
+        Some(Rect::new(3, 4, 18, 9))
+    );
+    rgb_buffer.fill(WHITE);
+    assert_eq!(
+        rgb_buffer.bounding_box_nonzero(),
+        Some(Rect::new(0, 0, X, Y))
+    );
+}
+
+#[test]
+fn write_column() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let colors = [RED, GREEN, BLUE];
+    rgb_buffer.write_column(5, &colors).unwrap();
+    assert_eq!(rgb_buffer.pixels()[0][5], RED);
+    assert_eq!(rgb_buffer.pixels()[2][5], BLUE);
+    assert_eq!(
+        rgb_buffer.bounding_box_nonzero(),
+        Some(Rect::new(5, 0, 1, 3))
+    );
+    assert!(matches!(
+        rgb_buffer.write_column(X, &colors),
+        Err(RgbBufferError::InvalidPosition { x: X, y: 0 })
+    ));
+    assert!(matches!(
+        rgb_buffer.write_column(0, &[RED; Y + 1]),
+        Err(RgbBufferError::InvalidSize { x: 1, y }) if y == Y + 1
+    ));
+    // Copy a row.
+    rgb_buffer.copy_row(1, Y - 1).unwrap();
+    assert_eq!(rgb_buffer.pixels()[Y - 1][5], GREEN);
+    assert_eq!(rgb_buffer.count_color(GREEN), 2);
+    assert!(rgb_buffer.copy_row(Y, 0).is_err());
+    assert!(rgb_buffer.copy_row(0, Y).is_err());
+}
+
+#[test]
+fn fill_triangle_clipped() {
+    let color = [0, 255, 20, 5];
+    // The same as `fill_triangle_gradient` with one color.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_triangle_clipped((3, 2), (30, 9), (12, 25), color);
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.fill_triangle_gradient((3, 2, color), (30, 9, color), (12, 25, color));
+    assert_eq!(rgb_buffer, expected);
+    // The winding order doesn't matter.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_triangle_clipped((12, 25), (30, 9), (3, 2), color);
+    assert_eq!(rgb_buffer, expected);
+    // A triangle that covers the whole buffer.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_triangle_clipped((-100, -100), (500, -100), (-100, 500), color);
+    assert_eq!(rgb_buffer.count_color(color), X * Y);
+    // A triangle that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_triangle_clipped((-10, 0), (10, 0), (-10, 20), color);
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(0, 0, 10, 10))
+    );
+    // Triangles that are out of bounds or collinear.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_triangle_clipped((-10, 0), (-1, 0), (-10, 20), color);
+    rgb_buffer.fill_triangle_clipped((0, 0), (5, 5), (10, 10), color);
+    assert_eq!(rgb_buffer.count_color(color), 0);
+}
+
+#[test]
+fn diff() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(GRAY);
+    let snapshot = rgb_buffer.snapshot();
+    assert_eq!(snapshot.len(), X * Y);
+    assert!(snapshot.iter().all(|c| *c == GRAY));
+    assert_eq!(rgb_buffer.diff(&snapshot).unwrap(), None);
+    rgb_buffer.pixels_mut()[4][20] = RED;
+    rgb_buffer.pixels_mut()[9][3] = RED;
+    assert_eq!(
+        rgb_buffer.diff(&snapshot).unwrap(),
+        Some(Rect::new(3, 4, 18, 6))
+    );
+    assert!(matches!(
+        rgb_buffer.diff(&snapshot[1..]),
+        Err(RgbBufferError::InvalidSize { x: X, y }) if y == X * Y - 1
+    ));
+}
+
+#[test]
+fn fill_ring() {
+    let color = [0, 255, 20, 5];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_ring(20, 15, 4, 9, color);
+    // The ring is the outer circle minus the inner circle.
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.fill_circle(20, 15, 9, color);
+    expected.fill_circle(20, 15, 4, [0; 4]);
+    assert_eq!(rgb_buffer, expected);
+    assert_eq!(rgb_buffer.pixels()[15][20], [0; 4]);
+    // A ring that is partially out of bounds.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ring(2, 2, 1, 5, color);
+    expected.fill([0; 4]);
+    expected.fill_circle(2, 2, 5, color);
+    expected.fill_circle(2, 2, 1, [0; 4]);
+    assert_eq!(rgb_buffer, expected);
+    // An inner radius of 0 leaves a 1-pixel hole.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ring(20, 15, 0, 3, color);
+    assert_eq!(rgb_buffer.pixels()[15][20], [0; 4]);
+    assert_eq!(rgb_buffer.pixels()[15][21], color);
+    // The inner radius must be less than the outer radius.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.fill_ring(20, 15, 5, 5, color);
+    assert_eq!(rgb_buffer.count_color(color), 0);
+}
+
+#[test]
+fn draw_context() {
+    let color = [0, 255, 20, 5];
+    // `Replace` draws like the `Canvas` methods.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let mut context = rgb_buffer.draw_context(BlendMode::default());
+    assert_eq!(context.mode(), BlendMode::Replace);
+    context.draw_line(3, 2, 30, 12, color);
+    context.fill_circle(X - 2, 10, 5, color);
+    context.fill_rect(Rect::new(5, Y - 3, 10, 10), color);
+    context.set_pixel(0, Y - 1, color);
+    context.set_pixel(X, 0, color);
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.draw_line(3, 2, 30, 12, color);
+    expected.fill_circle(X - 2, 10, 5, color);
+    expected.fill_rectangle(5, Y - 3, 10, 3, color);
+    expected.pixels_mut()[Y - 1][0] = color;
+    assert_eq!(rgb_buffer, expected);
+    // Lines with huge endpoints are clipped without stepping through every point.
+    rgb_buffer
+        .draw_context(BlendMode::Replace)
+        .draw_line(0, 5, usize::MAX / 4, 7, color);
+    expected.draw_line(0, 5, usize::MAX / 4, 7, color);
+    assert_eq!(rgb_buffer, expected);
+    // `Alpha` and `Add` draw like `blend_rectangle` and `add_rectangle`.
+    rgb_buffer.fill(GRAY);
+    expected.fill(GRAY);
+    let mut context = rgb_buffer.draw_context(BlendMode::Alpha(64));
+    context.fill_rect(Rect::new(2, 3, 10, 5), color);
+    context.set_mode(BlendMode::Add);
+    context.fill_rect(Rect::new(20, 3, 10, 5), color);
+    expected.blend_rectangle(2, 3, 10, 5, color, 64);
+    expected.add_rectangle(20, 3, 10, 5, color);
+    assert_eq!(rgb_buffer, expected);
+    // Overlapping multiplied draws get darker.
+    rgb_buffer.fill(WHITE);
+    let mut context = rgb_buffer.draw_context(BlendMode::Multiply);
+    context.set_pixel(0, 0, GRAY);
+    context.set_pixel(0, 0, GRAY);
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(64, 64, 64));
+    assert_eq!(
+        BlendMode::Multiply.apply([0, 200, 100, 50], [0, 255, 0, 128]),
+        [0, 200, 0, 25]
+    );
+}
+
+#[test]
+fn fill_stripes() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_vertical_stripes(3, RED, BLUE).unwrap();
+    assert_eq!(rgb_buffer.pixels()[Y - 1][2], RED);
+    assert_eq!(rgb_buffer.pixels()[0][3], BLUE);
+    assert_eq!(rgb_buffer.pixels()[5][6], RED);
+    // 40 columns are 13 stripes of 3 columns and 1 stripe of 1 column.
+    assert_eq!(rgb_buffer.count_color(RED), 21 * Y);
+    assert!(matches!(
+        rgb_buffer.fill_vertical_stripes(0, RED, BLUE),
+        Err(RgbBufferError::InvalidSize { x: 0, y: Y })
+    ));
+    rgb_buffer.fill_horizontal_stripes(4, GREEN, WHITE).unwrap();
+    assert_eq!(rgb_buffer.pixels()[3][X - 1], GREEN);
+    assert_eq!(rgb_buffer.pixels()[4][0], WHITE);
+    assert_eq!(rgb_buffer.count_color(GREEN), 16 * X);
+    assert!(matches!(
+        rgb_buffer.fill_horizontal_stripes(0, GREEN, WHITE),
+        Err(RgbBufferError::InvalidSize { x: X, y: 0 })
+    ));
+}
+
+#[test]
+fn row_bytes() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.pixels_mut()[3][1] = [0, 255, 20, 5];
+    let row = rgb_buffer.row_bytes(3).unwrap();
+    assert_eq!(row.len(), 4 * X);
+    assert_eq!(row[4..8], [0, 255, 20, 5]);
+    assert_eq!(rgb_buffer.row_bytes(Y), None);
+    // Modify a row.
+    rgb_buffer.row_bytes_mut(Y - 1).unwrap()[..4].copy_from_slice(&[0, 1, 2, 3]);
+    assert_eq!(rgb_buffer.pixels()[Y - 1][0], [0, 1, 2, 3]);
+    assert!(rgb_buffer.row_bytes_mut(Y).is_none());
+}
+
+#[test]
+fn draw_catmull_rom() {
+    let color = [0, 255, 20, 5];
+    let points = [(2.0, 20.0), (10.0, 5.0), (20.0, 25.0), (35.0, 10.0)];
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_catmull_rom(&points, color, 16);
+    // The curve passes through every point.
+    for (x, y) in points {
+        assert_eq!(rgb_buffer.pixels()[y as usize][x as usize], color);
+    }
+    assert_eq!(
+        rgb_buffer.bounding_box(color),
+        Some(Rect::new(2, 5, 34, 21))
+    );
+    // Collinear points are a straight line.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_catmull_rom(&[(0.0, 3.0), (10.0, 3.0), (20.0, 3.0)], color, 4);
+    assert_eq!(rgb_buffer.bounding_box(color), Some(Rect::new(0, 3, 21, 1)));
+    // Without segments, the points are connected with straight lines.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_catmull_rom(&[(3.0, 2.0), (30.0, 12.0)], color, 0);
+    let mut line = OwnedRgbBuffer::<X, Y>::new();
+    line.draw_line(3, 2, 30, 12, color);
+    assert_eq!(rgb_buffer, line);
+    // A single point doesn't draw anything.
+    rgb_buffer.fill([0; 4]);
+    rgb_buffer.draw_catmull_rom(&[(3.0, 2.0)], color, 4);
+    assert_eq!(rgb_buffer.count_color(color), 0);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "Position out of bounds")]
+fn pixel_unchecked_out_of_bounds() {
+    let rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    unsafe { rgb_buffer.pixel_unchecked(X, 0) };
+}
+
+#[test]
+fn fill_border() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(GRAY);
+    rgb_buffer.fill_border(Rect::new(2, 3, 10, 8), 2, RED);
+    assert_eq!(rgb_buffer.bounding_box(RED), Some(Rect::new(2, 3, 10, 8)));
+    assert_eq!(rgb_buffer.count_color(RED), 10 * 8 - 6 * 4);
+    assert_eq!(rgb_buffer.pixels()[5][4], GRAY);
+    assert_eq!(rgb_buffer.pixels()[4][3], RED);
+    // A thickness of 1 is the same as `draw_rect`.
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.fill(GRAY);
+    expected.draw_rect(Rect::new(2, 3, 10, 8), RED);
+    rgb_buffer.fill(GRAY);
+    rgb_buffer.fill_border(Rect::new(2, 3, 10, 8), 1, RED);
+    assert_eq!(rgb_buffer, expected);
+    // A thick border fills the whole rectangle.
+    rgb_buffer.fill(GRAY);
+    rgb_buffer.fill_border(Rect::new(2, 3, 10, 5), 100, RED);
+    assert_eq!(rgb_buffer.count_color(RED), 50);
+    // A border that is partially out of bounds.
+    rgb_buffer.fill(GRAY);
+    rgb_buffer.fill_border(Rect::new(X - 3, Y - 3, 6, 6), 1, RED);
+    assert_eq!(rgb_buffer.count_color(RED), 5);
+    rgb_buffer.fill_border(Rect::new(0, 0, 6, 6), 0, RED);
+    assert_eq!(rgb_buffer.count_color(RED), 5);
+}
+
+#[test]
+fn pixelate() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.shade(|x, y| if (x + y) % 2 == 0 { WHITE } else { BLACK });
+    let checkerboard = rgb_buffer.clone();
+    rgb_buffer.pixelate(1).unwrap();
+    assert_eq!(rgb_buffer, checkerboard);
+    assert!(matches!(
+        rgb_buffer.pixelate(0),
+        Err(RgbBufferError::InvalidSize { x: 0, y: 0 })
+    ));
+    // Each 4x4 block, and each 4x2 block at the bottom edge, is half white and half black.
+    rgb_buffer.pixelate(4).unwrap();
+    assert_eq!(rgb_buffer.count_color(rgb(128, 128, 128)), X * Y);
+    // A 3x3 block that starts on a white pixel has 5 white pixels.
+    rgb_buffer.copy_from(&checkerboard);
+    rgb_buffer.pixelate(3).unwrap();
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(142, 142, 142));
+    assert_eq!(rgb_buffer.pixels()[2][2], rgb(142, 142, 142));
+    assert_eq!(rgb_buffer.pixels()[0][3], rgb(113, 113, 113));
+    // The right column of blocks is 1 pixel wide.
+    assert_eq!(rgb_buffer.pixels()[0][X - 1], rgb(85, 85, 85));
+}
+
+#[test]
+fn load_rgb() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    let mut data = vec![0; X * Y * 3];
+    data[..6].copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+    data[X * Y * 3 - 3..].copy_from_slice(&[7, 8, 9]);
+    rgb_buffer.load_rgb(&data).unwrap();
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(1, 2, 3));
+    assert_eq!(rgb_buffer.pixels()[0][1], rgb(4, 5, 6));
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], rgb(7, 8, 9));
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y - 3);
+    assert!(matches!(
+        rgb_buffer.load_rgb(&data[1..]),
+        Err(RgbBufferError::InvalidSize { x: X, y }) if y == X * Y * 3 - 1
+    ));
+}
+
+#[test]
+fn adjust_saturation() {
+    let color = rgb(200, 100, 50);
+    let l = luminance(color);
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(color);
+    rgb_buffer.adjust_saturation(1.0);
+    assert_eq!(rgb_buffer.count_color(color), X * Y);
+    // Halfway to gray.
+    rgb_buffer.adjust_saturation(0.5);
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(162, 112, 87));
+    // Gray.
+    rgb_buffer.fill(color);
+    rgb_buffer.adjust_saturation(0.0);
+    assert_eq!(rgb_buffer.count_color(rgb(l, l, l)), X * Y);
+    // Boost the saturation. Channels are clamped.
+    rgb_buffer.fill(color);
+    rgb_buffer.adjust_saturation(3.0);
+    assert_eq!(rgb_buffer.pixels()[0][0], rgb(255, 52, 0));
+    // Huge amounts are clamped instead of overflowing.
+    for amount in [1.0e5, f32::INFINITY] {
+        rgb_buffer.fill(color);
+        rgb_buffer.adjust_saturation(amount);
+        assert_eq!(rgb_buffer.pixels()[0][0], rgb(255, 0, 0));
+    }
+}
+
+#[test]
+fn find_pixel() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    assert_eq!(rgb_buffer.find_pixel(|c| c == RED), None);
+    assert!(rgb_buffer.find_all(|c| c == RED).is_empty());
+    rgb_buffer.pixels_mut()[7][3] = RED;
+    rgb_buffer.pixels_mut()[2][30] = RED;
+    rgb_buffer.pixels_mut()[7][1] = [0, 200, 10, 10];
+    // Pixels are searched in row-major order.
+    assert_eq!(rgb_buffer.find_pixel(|c| c == RED), Some((30, 2)));
+    assert_eq!(
+        rgb_buffer.find_all(|c| c[1] > 100),
+        vec![(30, 2), (1, 7), (3, 7)]
+    );
+}
+
+#[test]
+fn blend_buffer() {
+    let mut src = OwnedRgbBuffer::<X, Y>::new();
+    src.fill_rect(Rect::new(2, 3, 4, 5), RED);
+    let mut dst = OwnedRgbBuffer::<X, Y>::new();
+    dst.fill(WHITE);
+    dst.blend_buffer(&src, 0);
+    assert_eq!(dst.count_color(WHITE), X * Y);
+    // Crossfade halfway.
+    dst.blend_buffer(&src, 128);
+    assert_eq!(dst.pixels()[3][2], rgb(255, 127, 127));
+    assert_eq!(dst.pixels()[0][0], rgb(127, 127, 127));
+    dst.blend_buffer(&src, 255);
+    assert_eq!(dst, src);
+}
+
+#[test]
+fn draw_text_centered() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    assert_eq!(rgb_buffer.measure_text("", 2), (0, 0));
+    assert_eq!(rgb_buffer.measure_text("Hi", 1), (16, 8));
+    assert_eq!(rgb_buffer.measure_text("Hi", 2), (32, 16));
+    rgb_buffer.draw_text_centered(20, 15, "Hi", WHITE, 2);
+    let mut expected = OwnedRgbBuffer::<X, Y>::new();
+    expected.draw_text_scaled(4, 7, "Hi", WHITE, 2);
+    assert_eq!(rgb_buffer, expected);
+    // Text that would start out of bounds is moved to the top-left corner.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_text_centered(0, 0, "Hi", WHITE, 1);
+    expected.fill(BLACK);
+    expected.draw_text(0, 0, "Hi", WHITE);
+    assert_eq!(rgb_buffer, expected);
+}
+
+#[test]
+fn fill_xor() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_xor();
+    assert_eq!(rgb_buffer.pixels()[0][0], BLACK);
+    assert_eq!(rgb_buffer.pixels()[5][3], rgb(6, 6, 6));
+    assert_eq!(rgb_buffer.pixels()[29][39], rgb(58, 58, 58));
+    assert_eq!(rgb_buffer.count_color(BLACK), Y);
+}
+
+#[test]
+fn fill_plasma() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_plasma(0.0);
+    let frame = rgb_buffer.snapshot();
+    // The plasma is deterministic.
+    rgb_buffer.fill_plasma(0.0);
+    assert_eq!(rgb_buffer.diff(&frame).unwrap(), None);
+    // The plasma animates.
+    rgb_buffer.fill_plasma(1.0);
+    assert!(rgb_buffer.diff(&frame).unwrap().is_some());
+    assert!(rgb_buffer.pixels().iter().flatten().all(|c| c[0] == 0));
+}
+
+#[test]
+fn rows_and_columns() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_xor();
+    assert_eq!(rgb_buffer.row(3), Some(&rgb_buffer.pixels()[3][..]));
+    assert_eq!(rgb_buffer.row(Y), None);
+    let column = rgb_buffer
+        .column_iter(5)
+        .unwrap()
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(column.len(), Y);
+    assert_eq!(column[2], rgb_buffer.pixels()[2][5]);
+    assert!(rgb_buffer.column_iter(X).is_none());
+    assert_eq!(rgb_buffer.columns().count(), X);
+    assert!(rgb_buffer
+        .columns()
+        .nth(5)
+        .unwrap()
+        .copied()
+        .eq(column.iter().copied()));
+}
+
+#[test]
+fn export() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.pixels_mut()[0][0] = rgb(1, 2, 3);
+    rgb_buffer.pixels_mut()[Y - 1][X - 1] = rgb(4, 5, 6);
+    let mut encoder = PpmEncoder::new(Vec::new());
+    rgb_buffer.export(&mut encoder).unwrap();
+    let ppm = encoder.into_inner();
+    let header = format!("P6\n{X} {Y}\n255\n");
+    assert_eq!(ppm.len(), header.len() + X * Y * 3);
+    assert!(ppm.starts_with(header.as_bytes()));
+    assert_eq!(ppm[header.len()..header.len() + 3], [1, 2, 3]);
+    assert_eq!(ppm[ppm.len() - 3..], [4, 5, 6]);
+}
+
+#[test]
+fn fill_circle_aa() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill_circle_aa(10.0, 10.0, 4.0, RED);
+    assert_eq!(rgb_buffer.pixels()[10][10], RED);
+    assert_eq!(rgb_buffer.pixels()[7][10], RED);
+    // This pixel is centered on the edge.
+    assert_eq!(rgb_buffer.pixels()[6][10], rgb(128, 0, 0));
+    assert_eq!(rgb_buffer.pixels()[5][10], BLACK);
+    assert_eq!(
+        rgb_buffer.bounding_box_nonzero(),
+        Some(Rect::new(6, 6, 9, 9))
+    );
+    // Circles can be partially out of bounds.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_circle_aa(-1.5, X as f32 + 10.0, 20.0, RED);
+    rgb_buffer.fill_circle_aa(X as f32 - 0.5, Y as f32 - 0.5, 2.0, RED);
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], RED);
+    // Nothing is drawn if the radius isn't positive.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.fill_circle_aa(10.0, 10.0, 0.0, RED);
+    rgb_buffer.fill_circle_aa(10.0, 10.0, f32::NAN, RED);
+    assert_eq!(rgb_buffer.bounding_box_nonzero(), None);
+}
+
+#[test]
+fn fill_gradient_linear() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    // Horizontal.
+    rgb_buffer.fill_gradient_linear((0.0, 0.0), ((X - 1) as f32, 0.0), BLACK, WHITE);
+    assert_eq!(rgb_buffer.count_color(BLACK), Y);
+    assert_eq!(rgb_buffer.count_color(WHITE), Y);
+    assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], WHITE);
+    // Diagonal. Points on the same perpendicular line have the same color.
+    rgb_buffer.fill_gradient_linear((0.0, 0.0), (10.0, 10.0), BLACK, WHITE);
+    assert_eq!(rgb_buffer.pixels()[0][10], rgb(128, 128, 128));
+    assert_eq!(rgb_buffer.pixels()[10][0], rgb(128, 128, 128));
+    assert_eq!(rgb_buffer.pixels()[5][5], rgb(128, 128, 128));
+    assert_eq!(rgb_buffer.pixels()[20][20], WHITE);
+    assert_eq!(rgb_buffer.count_color(BLACK), 1);
+    // The gradient is clamped before `start`.
+    rgb_buffer.fill_gradient_linear((20.0, 0.0), (30.0, 0.0), RED, BLUE);
+    assert_eq!(rgb_buffer.count_color(RED), 21 * Y);
+    // Degenerate.
+    rgb_buffer.fill_gradient_linear((5.0, 5.0), (5.0, 5.0), RED, BLUE);
+    assert_eq!(rgb_buffer.count_color(RED), X * Y);
+}
+
+#[test]
+fn mirror_quadrant() {
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.pixels_mut()[1][2] = RED;
+    rgb_buffer.pixels_mut()[Y - 1][X - 1] = BLUE;
+    rgb_buffer.mirror_quadrant();
+    assert_eq!(rgb_buffer.count_color(RED), 4);
+    assert_eq!(rgb_buffer.count_color(BLUE), 0);
+    for (x, y) in [(2, 1), (X - 3, 1), (2, Y - 2), (X - 3, Y - 2)] {
+        assert_eq!(rgb_buffer.pixels()[y][x], RED);
+    }
+    // The center row and column of odd dimensions belong to the top-left quadrant.
+    let mut odd = OwnedRgbBuffer::<5, 3>::new();
+    odd.pixels_mut()[1][2] = RED;
+    odd.pixels_mut()[0][1] = GREEN;
+    odd.pixels_mut()[1][4] = BLUE;
+    odd.mirror_quadrant();
+    assert_eq!(odd.count_color(RED), 1);
+    assert_eq!(odd.count_color(GREEN), 4);
+    assert_eq!(odd.pixels()[2][3], GREEN);
+    assert_eq!(odd.count_color(BLUE), 0);
+}
+
+#[test]
+fn draw_rect_dashed() {
+    let rect = Rect::new(2, 3, 4, 3);
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.draw_rect_dashed(rect, RED, 2, 1, 0);
+    assert_eq!(rgb_buffer.count_color(RED), 7);
+    // The pattern continues around the corners, clockwise from the top-left corner.
+    for (x, y) in [(4, 3), (5, 5), (2, 5)] {
+        assert_eq!(rgb_buffer.pixels()[y][x], BLACK);
+    }
+    for (x, y) in [(5, 3), (5, 4), (4, 5), (2, 4)] {
+        assert_eq!(rgb_buffer.pixels()[y][x], RED);
+    }
+    // Advance the phase.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_rect_dashed(rect, RED, 2, 1, 1);
+    for (x, y) in [(3, 3), (5, 4), (3, 5)] {
+        assert_eq!(rgb_buffer.pixels()[y][x], BLACK);
+    }
+    // Without gaps, this is the same as `draw_rect`.
+    let mut outline = OwnedRgbBuffer::<X, Y>::new();
+    for rect in [rect, Rect::new(10, 10, 1, 4), Rect::new(10, 20, 5, 1)] {
+        rgb_buffer.fill(BLACK);
+        rgb_buffer.draw_rect_dashed(rect, RED, 3, 0, 2);
+        outline.fill(BLACK);
+        outline.draw_rect(rect, RED);
+        assert_eq!(rgb_buffer, outline);
+    }
+    // Out of bounds pixels aren't drawn.
+    rgb_buffer.fill(BLACK);
+    rgb_buffer.draw_rect_dashed(Rect::new(X - 2, Y - 2, 10, 10), RED, 1, 0, 0);
+    assert_eq!(rgb_buffer.count_color(RED), 3);
+}
+
+#[test]
+fn accum_buffer() {
+    let mut accum = AccumBuffer::<X, Y>::new();
+    for _ in 0..4 {
+        accum.add(1, 1, WHITE);
+    }
+    accum.add(2, 2, RED);
+    accum.add(X, 0, RED);
+    assert_eq!(accum.pixels()[1][1], [0.0, 4.0, 4.0, 4.0]);
+    // Resolving replaces every pixel.
+    let mut rgb_buffer = OwnedRgbBuffer::<X, Y>::new();
+    rgb_buffer.fill(BLUE);
+    accum.resolve_into(&mut rgb_buffer, 1.0);
+    // Bright pixels don't clip.
+    assert_eq!(rgb_buffer.pixels()[1][1], rgb(250, 250, 250));
+    assert_eq!(rgb_buffer.pixels()[2][2], rgb(161, 0, 0));
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y - 2);
+    accum.clear();
+    accum.resolve_into(&mut rgb_buffer, 1.0);
+    assert_eq!(rgb_buffer.count_color(BLACK), X * Y);
+}
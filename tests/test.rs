@@ -6,7 +6,10 @@ use winit::event::{StartCause, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, EventLoop};
 use winit::window::{Window, WindowAttributes, WindowId};
 
-use softbuffer_rgb::RgbBuffer;
+use softbuffer_rgb::{
+    premultiply, to_u32, unpremultiply, Canvas, Channel, OwnedRgbBuffer, Rect, RgbBuffer,
+    RgbBufferError, RgbSurface,
+};
 
 const X: usize = 400;
 const Y: usize = 300;
@@ -48,15 +51,178 @@ impl ApplicationHandler for App {
             let x = 12;
             let y = 14;
             let color = [0, 255, 20, 5];
-            rgb_buffer.pixels[y][x] = color;
+            rgb_buffer.pixels_mut()[y][x] = color;
             // Check that the pixel was set.
             let sb_color = u32::from_le_bytes(color);
-            assert_eq!(rgb_buffer.buffer[index(x, y)], sb_color);
+            assert_eq!(
+                rgb_buffer.with_buffer(|buffer| buffer[index(x, y)]),
+                sb_color
+            );
+            // Modify the raw buffer.
+            let len = rgb_buffer.with_buffer(|buffer| {
+                buffer[index(x + 1, y)] = sb_color;
+                buffer.len()
+            });
+            assert_eq!(len, X * Y);
+            assert_eq!(rgb_buffer.pixels()[y][x + 1], color);
+            // Invert the colors.
+            rgb_buffer.map_pixels(|c| [0, 255 - c[1], 255 - c[2], 255 - c[3]]);
+            assert_eq!(rgb_buffer.pixels()[y][x], [0, 0, 235, 250]);
+            // Modify the channels.
+            rgb_buffer.set_channel(Channel::G, 100);
+            rgb_buffer.map_channel(Channel::B, |b| b / 2);
+            assert_eq!(rgb_buffer.pixels()[y][x], [0, 0, 100, 125]);
+            rgb_buffer.set_channel(Channel::G, 235);
+            rgb_buffer.map_channel(Channel::B, |_| 250);
+            #[cfg(feature = "rayon")]
+            {
+                rgb_buffer.par_map_pixels(|c| [0, 255 - c[1], 255 - c[2], 255 - c[3]]);
+                assert_eq!(rgb_buffer.pixels()[y][x], color);
+            }
             // This is ok.
             rgb_buffer.fill_rectangle(20, 20, 200, 50, [0, 67, 200, 80]);
+            // Test the rectangle helpers.
+            let rect = Rect::new(10, 20, 30, 40);
+            assert!(rect.contains(10, 20));
+            assert!(rect.contains(39, 59));
+            assert!(!rect.contains(40, 59));
+            assert_eq!(
+                rect.intersection(&Rect::new(30, 50, 100, 100)),
+                Some(Rect::new(30, 50, 10, 10))
+            );
+            assert_eq!(rect.intersection(&Rect::new(40, 20, 10, 10)), None);
+            assert_eq!(
+                Rect::new(390, 290, 20, 20).clamp_to(X, Y),
+                Rect::new(390, 290, 10, 10)
+            );
+            // Premultiply colors. Precision is lost for small alpha values.
+            assert_eq!(premultiply([0, 255, 128, 0], 128), [0, 128, 64, 0]);
+            for alpha in [255, 128, 64] {
+                for v in 0..=255 {
+                    let c = unpremultiply(premultiply([0, v, v, v], alpha), alpha);
+                    assert_eq!(c[0], 0);
+                    assert!(c[1].abs_diff(v) <= 255 / alpha);
+                }
+            }
+            assert_eq!(unpremultiply(color, 0), [0, 0, 0, 0]);
+            // Clear the buffer, fill a rectangle, and then draw its outline.
+            rgb_buffer.fill([0, 0, 0, 0]);
+            let outline_color = [0, 1, 2, 3];
+            let text_color = [0, 4, 5, 6];
+            rgb_buffer.fill_rect(rect, color);
+            rgb_buffer.draw_rect(rect, outline_color);
+            assert_eq!(rgb_buffer.pixels()[20][10], outline_color);
+            assert_eq!(rgb_buffer.pixels()[59][39], outline_color);
+            assert_eq!(rgb_buffer.pixels()[21][11], color);
+            // Count the pixels of the outline.
+            assert_eq!(rgb_buffer.count_color(outline_color), 2 * 30 + 2 * 38);
+            assert_eq!(rgb_buffer.bounding_box(outline_color), Some(rect));
+            assert_eq!(rgb_buffer.bounding_box([0, 9, 9, 9]), None);
+            // Tile a 2x3 pattern across the buffer.
+            let pattern = [
+                [0, 1, 1, 1],
+                [0, 2, 2, 2],
+                [0, 3, 3, 3],
+                [0, 4, 4, 4],
+                [0, 5, 5, 5],
+                [0, 6, 6, 6],
+            ];
+            assert!(rgb_buffer.fill_pattern(4, &pattern).is_err());
+            rgb_buffer.fill_pattern(2, &pattern).unwrap();
+            assert_eq!(rgb_buffer.pixels()[0][0], pattern[0]);
+            assert_eq!(rgb_buffer.pixels()[4][5], pattern[3]);
+            assert_eq!(rgb_buffer.count_color(pattern[5]), (X / 2) * (Y / 3));
+            rgb_buffer.fill([0, 0, 0, 0]);
+            // Draw in a region that is partially out of bounds.
+            let mut region = rgb_buffer.region_mut(Rect::new(X - 10, 50, 20, 5));
+            assert_eq!((region.width(), region.height()), (10, 5));
+            region.fill(color);
+            region.fill_rect(Rect::new(8, 3, 100, 100), outline_color);
+            region.set_pixel(0, 0, text_color);
+            region.set_pixel(10, 0, text_color);
+            assert_eq!(region.get_pixel(9, 4), Some(outline_color));
+            assert_eq!(region.get_pixel(10, 4), None);
+            assert_eq!(rgb_buffer.pixels()[50][X - 10], text_color);
+            assert_eq!(rgb_buffer.count_color(text_color), 1);
+            assert_eq!(rgb_buffer.count_color(outline_color), 4);
+            assert_eq!(rgb_buffer.count_color(color), 45);
+            rgb_buffer.fill([0, 0, 0, 0]);
+            // Draw an ellipse.
+            rgb_buffer.draw_ellipse(100, 100, 40, 20, color);
+            assert_eq!(
+                rgb_buffer.bounding_box(color),
+                Some(Rect::new(60, 80, 81, 41))
+            );
+            rgb_buffer.fill([0, 0, 0, 0]);
+            // Fill rows at the bottom of the buffer. This is clipped.
+            let band_color = [0, 7, 8, 9];
+            rgb_buffer.fill_rows(Y - 2, 10, band_color);
+            assert_eq!(rgb_buffer.count_color(band_color), 2 * X);
+            // Draw text. The last character is partially out of bounds.
+            rgb_buffer.draw_text(X - 12, 100, "Hi", text_color);
+            assert_eq!(
+                rgb_buffer.bounding_box(text_color),
+                Some(Rect::new(X - 12, 100, 12, 7))
+            );
+            // Save a PNG file.
+            #[cfg(feature = "png")]
+            {
+                let path = std::env::temp_dir().join("softbuffer_rgb_test.png");
+                rgb_buffer.save_png(&path).unwrap();
+                let png = std::fs::read(&path).unwrap();
+                assert_eq!(png[..8], [137, 80, 78, 71, 13, 10, 26, 10]);
+                std::fs::remove_file(path).unwrap();
+            }
+            // Copy an owned buffer into the softbuffer buffer.
+            let mut owned = OwnedRgbBuffer::<X, Y>::new();
+            owned.fill_rect(rect, color);
+            rgb_buffer.copy_from(&owned);
+            assert_eq!(rgb_buffer.pixels(), owned.pixels());
+            // Fill with u32 colors.
+            rgb_buffer.fill_u32(to_u32(text_color));
+            rgb_buffer.fill_rectangle_u32(X - 4, Y - 3, 4, 3, sb_color);
+            assert_eq!(rgb_buffer.count_color(text_color), X * Y - 12);
+            assert_eq!(
+                rgb_buffer.bounding_box(color),
+                Some(Rect::new(X - 4, Y - 3, 4, 3))
+            );
+            // Clear a rectangle that is partially out of bounds.
+            rgb_buffer.clear_rectangle(X - 2, Y - 1, 10, 10);
+            assert_eq!(rgb_buffer.count_color([0, 0, 0, 0]), 2);
+            assert_eq!(rgb_buffer.pixels()[Y - 1][X - 1], [0, 0, 0, 0]);
             // Test the fill value.
             rgb_buffer.fill(color);
-            assert!(rgb_buffer.buffer.iter().all(|v| *v == sb_color));
+            assert!(rgb_buffer.with_buffer(|buffer| buffer.iter().all(|v| *v == sb_color)));
+            rgb_buffer.present().unwrap();
+            // Present part of the buffer.
+            let mut rgb_buffer =
+                RgbBuffer::<X, Y, _, _>::from_softbuffer(surface.buffer_mut().unwrap()).unwrap();
+            rgb_buffer.fill_rect(rect, outline_color);
+            rgb_buffer
+                .present_region(Rect::new(X - 10, 0, 20, 20))
+                .unwrap();
+            // Clear the buffer on construction.
+            {
+                let rgb_buffer =
+                    RgbBuffer::<X, Y, _, _>::from_softbuffer_zeroed(surface.buffer_mut().unwrap())
+                        .unwrap();
+                assert_eq!(rgb_buffer.count_color([0; 4]), X * Y);
+            }
+            // The error says how many pixels the buffer has.
+            assert!(matches!(
+                RgbBuffer::<X, 1, _, _>::from_softbuffer_zeroed(surface.buffer_mut().unwrap()),
+                Err(RgbBufferError::SizeMismatch { expected: X, actual }) if actual == X * Y
+            ));
+            // Draw a frame with an `RgbSurface`.
+            let mut rgb_surface = RgbSurface::<X, Y, _, _>::new(surface);
+            let mut drawn = false;
+            rgb_surface
+                .frame(|rgb_buffer| {
+                    rgb_buffer.fill(color);
+                    drawn = true;
+                })
+                .unwrap();
+            assert!(drawn);
             // End.
             event_loop.exit();
         }